@@ -10,8 +10,10 @@ use envlock::commands::alias::{
 use envlock::commands::plugin::{PluginRunOptions, run as run_plugin};
 use envlock::commands::preview::{PreviewOutputMode, run as run_preview};
 use envlock::commands::profiles::{
-    InitProfileType, ProfilesInitOptions, run_init as run_profiles_init,
-    run_status as run_profiles_status,
+    InitProfileType, LintOutputMode, ProfilesFmtOptions, ProfilesInitOptions, ProfilesLintOptions,
+    ProfilesMigrateOptions, run_fmt as run_profiles_fmt, run_init as run_profiles_init,
+    run_lint as run_profiles_lint, run_migrate as run_profiles_migrate,
+    run_schema as run_profiles_schema, run_status as run_profiles_status,
 };
 use envlock::commands::self_update::{SelfUpdateOptions, run as run_self_update};
 use envlock::commands::skill::{SkillInstallOptions, run_install as run_skill_install};
@@ -19,6 +21,7 @@ use envlock::core::app::App;
 use envlock::core::config::{
     CliInput, LogFormat as RuntimeLogFormat, OutputMode, RawEnv, RuntimeConfig,
 };
+use envlock::core::profile::{LoadOptions, ProfileFormat};
 use envlock::logging::{SessionLog, current_log_file, make_file_writer, prepare_session_log};
 use envlock::plugins::host::plugin_exit_code;
 use envlock::run;
@@ -28,7 +31,7 @@ use tracing_subscriber::{EnvFilter, prelude::*};
 #[command(
     name = "envlock",
     version,
-    about = "Build environment sessions from JSON profile",
+    about = "Build environment sessions from JSON, YAML or TOML profiles",
     after_help = "Docs: https://perishcode.github.io/envlock/"
 )]
 struct Cli {
@@ -61,6 +64,35 @@ struct ProfilesArgs {
 enum ProfilesSubcommand {
     Status,
     Init(ProfilesInitArgs),
+    Schema,
+    Migrate(ProfilesMigrateArgs),
+    Fmt(ProfilesFmtArgs),
+    Lint(ProfilesLintArgs),
+}
+
+#[derive(Debug, Args)]
+struct ProfilesLintArgs {
+    paths: Vec<PathBuf>,
+
+    #[arg(long = "output", default_value = "text", value_enum)]
+    output: PreviewOutputFormat,
+}
+
+#[derive(Debug, Args)]
+struct ProfilesFmtArgs {
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+
+    #[arg(long = "check")]
+    check: bool,
+}
+
+#[derive(Debug, Args)]
+struct ProfilesMigrateArgs {
+    path: PathBuf,
+
+    #[arg(long = "write")]
+    write: bool,
 }
 
 #[derive(Debug, Args)]
@@ -68,6 +100,10 @@ struct ProfilesInitArgs {
     #[arg(long = "type", value_enum, default_value = "minimal")]
     profile_type: ProfileTemplateType,
 
+    /// Defaults to json, or to the extension given in `--name`.
+    #[arg(long = "format", value_enum)]
+    format: Option<ProfileFileFormat>,
+
     #[arg(long = "name")]
     name: Option<String>,
 
@@ -100,6 +136,9 @@ struct AliasAppendArgs {
 struct AliasRunArgs {
     name: String,
 
+    #[command(flatten)]
+    overrides: RunOverrides,
+
     #[arg(trailing_var_arg = true)]
     command: Vec<String>,
 }
@@ -151,17 +190,49 @@ struct SelfUpdateArgs {
 
 #[derive(Debug, Args)]
 struct PreviewArgs {
-    #[arg(short = 'p', long = "profile")]
-    profile: PathBuf,
+    #[arg(short = 'p', long = "profile", required = true)]
+    profile: Vec<PathBuf>,
 
     #[arg(long = "output", default_value = "text", value_enum)]
     output: PreviewOutputFormat,
+
+    #[command(flatten)]
+    overrides: RunOverrides,
+
+    #[arg(long = "stage")]
+    stage: Option<String>,
+
+    #[arg(long = "strict")]
+    strict: bool,
+}
+
+/// Per-run profile adjustments shared by the run and alias run paths.
+#[derive(Debug, Default, Clone, Args)]
+struct RunOverrides {
+    #[arg(long = "set", value_name = "NAME=VALUE", value_parser = parse_param)]
+    set: Vec<(String, String)>,
+
+    #[arg(long = "enable", value_name = "ID")]
+    enable: Vec<String>,
+
+    #[arg(long = "disable", value_name = "ID")]
+    disable: Vec<String>,
+}
+
+impl RunOverrides {
+    fn merged(&self, other: &RunOverrides) -> RunOverrides {
+        RunOverrides {
+            set: [self.set.as_slice(), other.set.as_slice()].concat(),
+            enable: [self.enable.as_slice(), other.enable.as_slice()].concat(),
+            disable: [self.disable.as_slice(), other.disable.as_slice()].concat(),
+        }
+    }
 }
 
 #[derive(Debug, Args)]
 struct RunArgs {
     #[arg(short = 'p', long = "profile")]
-    profile: Option<PathBuf>,
+    profile: Vec<PathBuf>,
 
     #[arg(long = "output", default_value = "shell", value_enum)]
     output: OutputFormat,
@@ -169,6 +240,12 @@ struct RunArgs {
     #[arg(long = "strict")]
     strict: bool,
 
+    #[arg(long = "stage")]
+    stage: Option<String>,
+
+    #[command(flatten)]
+    overrides: RunOverrides,
+
     #[arg(long = "log-level", default_value = "warn", value_enum)]
     log_level: LogLevel,
 
@@ -205,6 +282,14 @@ fn main() -> Result<()> {
             }),
             Commands::Preview(args) => run_preview(
                 &args.profile,
+                &LoadOptions {
+                    params: args.overrides.set.into_iter().collect(),
+                    stage: args.stage.or(raw_env.stage.clone()),
+                    require_overlay: args.strict,
+                    ..LoadOptions::default()
+                },
+                &args.overrides.enable,
+                &args.overrides.disable,
                 match args.output {
                     PreviewOutputFormat::Text => PreviewOutputMode::Text,
                     PreviewOutputFormat::Json => PreviewOutputMode::Json,
@@ -212,11 +297,34 @@ fn main() -> Result<()> {
             ),
             Commands::Profiles(args) => match args.command {
                 ProfilesSubcommand::Status => run_profiles_status(),
+                ProfilesSubcommand::Schema => run_profiles_schema(),
+                ProfilesSubcommand::Migrate(migrate) => {
+                    run_profiles_migrate(ProfilesMigrateOptions {
+                        path: migrate.path,
+                        write: migrate.write,
+                    })
+                }
+                ProfilesSubcommand::Fmt(fmt) => run_profiles_fmt(ProfilesFmtOptions {
+                    paths: fmt.paths,
+                    check: fmt.check,
+                }),
+                ProfilesSubcommand::Lint(lint) => run_profiles_lint(ProfilesLintOptions {
+                    paths: lint.paths,
+                    output: match lint.output {
+                        PreviewOutputFormat::Text => LintOutputMode::Text,
+                        PreviewOutputFormat::Json => LintOutputMode::Json,
+                    },
+                }),
                 ProfilesSubcommand::Init(init) => run_profiles_init(ProfilesInitOptions {
                     profile_type: match init.profile_type {
                         ProfileTemplateType::Minimal => InitProfileType::Minimal,
                         ProfileTemplateType::Sample => InitProfileType::Sample,
                     },
+                    format: init.format.map(|format| match format {
+                        ProfileFileFormat::Json => ProfileFormat::Json,
+                        ProfileFileFormat::Yaml => ProfileFormat::Yaml,
+                        ProfileFileFormat::Toml => ProfileFormat::Toml,
+                    }),
                     name: init.name,
                     force: init.force,
                 }),
@@ -227,9 +335,12 @@ fn main() -> Result<()> {
                     name: append.name,
                     profile: append.profile,
                 }),
-                AliasSubcommand::Run(run_args) => {
-                    run_alias_named(&run_args.name, &cli.run_args, Some(run_args.command))
-                }
+                AliasSubcommand::Run(alias_args) => run_alias_named(
+                    &alias_args.name,
+                    &cli.run_args,
+                    &alias_args.overrides,
+                    Some(alias_args.command),
+                ),
             },
             Commands::Skill(args) => match args.command {
                 SkillSubcommand::Install(install) => run_skill_install(SkillInstallOptions {
@@ -256,10 +367,15 @@ fn main() -> Result<()> {
         } else {
             Some(Vec::new())
         };
-        return finish_command(run_alias_named(alias_name, &cli.run_args, command));
+        return finish_command(run_alias_named(
+            alias_name,
+            &cli.run_args,
+            &RunOverrides::default(),
+            command,
+        ));
     }
 
-    let config = match build_runtime_config(&cli.run_args, None, None) {
+    let config = match build_runtime_config(&cli.run_args, None, &RunOverrides::default(), None) {
         Ok(config) => config,
         Err(error) => return finish_command(Err(error)),
     };
@@ -306,12 +422,13 @@ fn run_external_command(tokens: &[String], run_args: &RunArgs) -> Result<()> {
     } else {
         Some(Vec::new())
     };
-    run_alias_named(alias_name, run_args, command)
+    run_alias_named(alias_name, run_args, &RunOverrides::default(), command)
 }
 
 fn run_alias_named(
     alias_name: &str,
     run_args: &RunArgs,
+    extra: &RunOverrides,
     command_override: Option<Vec<String>>,
 ) -> Result<()> {
     let profile = resolve_profile_for_alias(alias_name)?;
@@ -325,7 +442,12 @@ fn run_alias_named(
         }
         command
     });
-    let config = build_runtime_config(run_args, Some(PathBuf::from(profile)), command_override)?;
+    let config = build_runtime_config(
+        run_args,
+        Some(PathBuf::from(profile)),
+        extra,
+        command_override,
+    )?;
     let app = App::new(config);
     let result = run(&app)?;
     if let Some(code) = result.exit_code {
@@ -346,11 +468,15 @@ fn parse_shortcut_alias_name(tokens: &[String]) -> Option<&str> {
 fn build_runtime_config(
     run_args: &RunArgs,
     profile_override: Option<PathBuf>,
+    extra: &RunOverrides,
     command_override: Option<Vec<String>>,
 ) -> Result<RuntimeConfig> {
+    let overrides = run_args.overrides.merged(extra);
     RuntimeConfig::from_cli_and_env(
         CliInput {
-            profile: profile_override.or_else(|| run_args.profile.clone()),
+            profiles: profile_override
+                .map(|profile| vec![profile])
+                .unwrap_or_else(|| run_args.profile.clone()),
             output_mode: match run_args.output {
                 OutputFormat::Shell => OutputMode::Shell,
                 OutputFormat::Json => OutputMode::Json,
@@ -362,11 +488,24 @@ fn build_runtime_config(
                 LogFormat::Json => RuntimeLogFormat::Json,
             },
             command: command_override.unwrap_or_else(|| run_args.command.clone()),
+            params: overrides.set.into_iter().collect(),
+            enable_ids: overrides.enable,
+            disable_ids: overrides.disable,
+            stage: run_args.stage.clone(),
         },
         RawEnv::from_process(),
     )
 }
 
+fn parse_param(raw: &str) -> Result<(String, String), String> {
+    match raw.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected NAME=VALUE, got `{raw}`")),
+    }
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum OutputFormat {
     Shell,
@@ -412,6 +551,13 @@ enum ProfileTemplateType {
     Sample,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ProfileFileFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
 fn init_logging(
     level: tracing_subscriber::filter::LevelFilter,
     format: RuntimeLogFormat,
@@ -1,10 +1,12 @@
-use std::collections::BTreeSet;
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 use anyhow::Result;
 use serde::Serialize;
 
-use crate::core::profile::{EnvProfile, InjectionProfile};
+use crate::core::app::ProcessEnv;
+use crate::core::injections::{plan, skip_reason};
+use crate::core::profile::{EnvOpProfile, EnvProfile, InjectionProfile, LoadOptions, ProfileMeta};
 
 #[derive(Debug, Clone, Copy)]
 pub enum PreviewOutputMode {
@@ -15,7 +17,20 @@ pub enum PreviewOutputMode {
 #[derive(Debug, Serialize)]
 struct PreviewReport {
     profile: String,
-    injections: Vec<PreviewInjection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    meta: Option<ProfileMeta>,
+    injections: Vec<PreviewEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct PreviewEntry {
+    #[serde(flatten)]
+    injection: PreviewInjection,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    origin: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -24,6 +39,12 @@ enum PreviewInjection {
     Env {
         enabled: bool,
         keys: Vec<String>,
+        /// Keys the block leaves unset.
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        unset: Vec<String>,
+        /// One line per op, in order, e.g. `copy JAVA_HOME->JDK_HOME`.
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        ops: Vec<String>,
     },
     Command {
         enabled: bool,
@@ -39,8 +60,14 @@ enum PreviewInjection {
     },
 }
 
-pub fn run(profile_path: &Path, output_mode: PreviewOutputMode) -> Result<()> {
-    let report = build_report(profile_path)?;
+pub fn run(
+    profile_paths: &[PathBuf],
+    options: &LoadOptions,
+    enable: &[String],
+    disable: &[String],
+    output_mode: PreviewOutputMode,
+) -> Result<()> {
+    let report = build_report(profile_paths, options, enable, disable)?;
 
     match output_mode {
         PreviewOutputMode::Text => print_text(&report),
@@ -50,21 +77,46 @@ pub fn run(profile_path: &Path, output_mode: PreviewOutputMode) -> Result<()> {
     Ok(())
 }
 
-fn build_report(profile_path: &Path) -> Result<PreviewReport> {
-    let profile = crate::core::profile::load(profile_path)?;
-    let injections = profile.injections.into_iter().map(map_injection).collect();
+/// Lists injections in the order `run` would execute them.
+fn build_report(
+    profile_paths: &[PathBuf],
+    options: &LoadOptions,
+    enable: &[String],
+    disable: &[String],
+) -> Result<PreviewReport> {
+    let profile = crate::core::profile::load_stack(profile_paths, options)?;
+    let injections = plan(profile.injections, enable, disable)?
+        .into_iter()
+        .filter_map(map_injection)
+        .collect();
     Ok(PreviewReport {
-        profile: profile_path.display().to_string(),
+        profile: profile_paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        meta: profile.meta,
         injections,
     })
 }
 
-fn map_injection(injection: InjectionProfile) -> PreviewInjection {
-    match injection {
-        InjectionProfile::Env(env) => PreviewInjection::Env {
-            enabled: env.enabled,
-            keys: collect_env_keys(env),
-        },
+fn map_injection(injection: InjectionProfile) -> Option<PreviewEntry> {
+    let origin = injection
+        .origin()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
+    let skipped = skip_reason(&injection, &ProcessEnv);
+    let id = injection.id().map(str::to_string);
+    let mapped = match injection {
+        InjectionProfile::Env(env) => {
+            let (keys, unset) = collect_env_keys(&env);
+            PreviewInjection::Env {
+                enabled: env.enabled,
+                keys,
+                unset,
+                ops: env.ops.iter().map(describe_op).collect(),
+            }
+        }
         InjectionProfile::Command(command) => PreviewInjection::Command {
             enabled: command.enabled,
             program: command.program,
@@ -77,48 +129,112 @@ fn map_injection(injection: InjectionProfile) -> PreviewInjection {
             on_exist: format!("{:?}", symlink.on_exist).to_lowercase(),
             cleanup: symlink.cleanup,
         },
-    }
+        InjectionProfile::Include(_) => return None,
+    };
+    Some(PreviewEntry {
+        injection: mapped,
+        id,
+        origin,
+        skipped,
+    })
 }
 
-fn collect_env_keys(env: EnvProfile) -> Vec<String> {
-    let mut keys: BTreeSet<String> = env.vars.keys().cloned().collect();
-    for op in env.ops {
-        keys.insert(op.key().to_string());
+/// Splits the block's keys by their final state: exported or unset.
+fn collect_env_keys(env: &EnvProfile) -> (Vec<String>, Vec<String>) {
+    let mut unset_by_key: BTreeMap<&str, bool> =
+        env.vars.keys().map(|key| (key.as_str(), false)).collect();
+    for op in &env.ops {
+        if let EnvOpProfile::Move { from, .. } = op {
+            unset_by_key.insert(from, true);
+        }
+        unset_by_key.insert(op.key(), matches!(op, EnvOpProfile::Unset { .. }));
     }
-    keys.into_iter().collect()
+    let (unset, keys): (Vec<_>, Vec<_>) = unset_by_key.into_iter().partition(|(_, unset)| *unset);
+    let names = |entries: Vec<(&str, bool)>| {
+        entries
+            .into_iter()
+            .map(|(key, _)| key.to_string())
+            .collect()
+    };
+    (names(keys), names(unset))
+}
+
+/// Names the op and the keys it touches; values stay hidden.
+fn describe_op(op: &EnvOpProfile) -> String {
+    let name = match op {
+        EnvOpProfile::Set { .. } => "set",
+        EnvOpProfile::SetIfAbsent { .. } => "set_if_absent",
+        EnvOpProfile::Prepend { .. } => "prepend",
+        EnvOpProfile::Append { .. } => "append",
+        EnvOpProfile::Remove { .. } => "remove",
+        EnvOpProfile::Normalize { .. } => "normalize",
+        EnvOpProfile::SetEmpty { .. } => "set_empty",
+        EnvOpProfile::Copy { from, to } => return format!("copy {from}->{to}"),
+        EnvOpProfile::Move { from, to } => return format!("move {from}->{to}"),
+        EnvOpProfile::Replace { .. } => "replace",
+        EnvOpProfile::Unset { .. } => "unset",
+    };
+    format!("{name} {}", op.key())
 }
 
 fn print_text(report: &PreviewReport) {
     println!("profile: {}", report.profile);
+    if let Some(meta) = &report.meta {
+        if let Some(name) = &meta.name {
+            println!("name: {}", name);
+        }
+        if let Some(description) = &meta.description {
+            println!("description: {}", description);
+        }
+        if !meta.tags.is_empty() {
+            println!("tags: {}", meta.tags.join(", "));
+        }
+    }
     println!("injections: {}", report.injections.len());
 
-    for injection in &report.injections {
-        match injection {
-            PreviewInjection::Env { enabled, keys } => {
-                println!("- [env] enabled={} keys=[{}]", enabled, keys.join(", "));
+    for entry in &report.injections {
+        let line = match &entry.injection {
+            PreviewInjection::Env {
+                enabled,
+                keys,
+                unset,
+                ops,
+            } => {
+                let mut line = format!("- [env] enabled={} keys=[{}]", enabled, keys.join(", "));
+                if !unset.is_empty() {
+                    line = format!("{} unset=[{}]", line, unset.join(", "));
+                }
+                if !ops.is_empty() {
+                    line = format!("{} ops=[{}]", line, ops.join(", "));
+                }
+                line
             }
             PreviewInjection::Command {
                 enabled,
                 program,
                 arg_count,
-            } => {
-                println!(
-                    "- [command] enabled={} program={} arg_count={}",
-                    enabled, program, arg_count
-                );
-            }
+            } => format!(
+                "- [command] enabled={} program={} arg_count={}",
+                enabled, program, arg_count
+            ),
             PreviewInjection::Symlink {
                 enabled,
                 source,
                 target,
                 on_exist,
                 cleanup,
-            } => {
-                println!(
-                    "- [symlink] enabled={} source={} target={} on_exist={} cleanup={}",
-                    enabled, source, target, on_exist, cleanup
-                );
-            }
+            } => format!(
+                "- [symlink] enabled={} source={} target={} on_exist={} cleanup={}",
+                enabled, source, target, on_exist, cleanup
+            ),
+        };
+        let line = match &entry.id {
+            Some(id) => format!("{} id={}", line, id),
+            None => line,
+        };
+        match &entry.skipped {
+            Some(reason) => println!("{} origin={} skipped=\"{}\"", line, entry.origin, reason),
+            None => println!("{} origin={}", line, entry.origin),
         }
     }
 }
@@ -1,8 +1,18 @@
-use std::path::PathBuf;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use similar::TextDiff;
 
-use crate::core::config::{RawEnv, resolve_envlock_home};
+use crate::core::config::{
+    RawEnv, default_profile_path, resolve_envlock_home, resolve_profile_path, resolve_resource_home,
+};
+use crate::core::lint::{LintContext, LintFinding, lint};
+use crate::core::profile::{
+    CURRENT_SCHEMA_VERSION, LoadOptions, ProfileFormat, ProfileMeta, canonicalize, is_profile_path,
+    list_overlays,
+};
 
 pub enum InitProfileType {
     Minimal,
@@ -11,14 +21,38 @@ pub enum InitProfileType {
 
 pub struct ProfilesInitOptions {
     pub profile_type: InitProfileType,
+    /// `None` when `--format` was not given.
+    pub format: Option<ProfileFormat>,
     pub name: Option<String>,
     pub force: bool,
 }
 
+pub struct ProfilesMigrateOptions {
+    pub path: PathBuf,
+    pub write: bool,
+}
+
+pub enum LintOutputMode {
+    Text,
+    Json,
+}
+
+pub struct ProfilesLintOptions {
+    /// Profiles to lint; empty means the active profile.
+    pub paths: Vec<PathBuf>,
+    pub output: LintOutputMode,
+}
+
+pub struct ProfilesFmtOptions {
+    pub paths: Vec<PathBuf>,
+    pub check: bool,
+}
+
 pub fn run_status() -> Result<()> {
-    let envlock_home = resolve_envlock_home(&RawEnv::from_process())?;
+    let env = RawEnv::from_process();
+    let envlock_home = resolve_envlock_home(&env)?;
     let profiles_dir = envlock_home.join("profiles");
-    let default_profile = profiles_dir.join("default.json");
+    let default_profile = default_profile_path(&envlock_home);
 
     println!("envlock_home: {}", envlock_home.display());
     println!("profiles_dir: {}", profiles_dir.display());
@@ -31,6 +65,12 @@ pub fn run_status() -> Result<()> {
             "missing"
         }
     );
+    let (active_profile, source) = resolve_profile_path(&env, &envlock_home);
+    println!(
+        "active_profile: {} ({})",
+        active_profile.display(),
+        source.as_str()
+    );
 
     if !profiles_dir.is_dir() {
         println!("profiles_count: 0");
@@ -45,8 +85,15 @@ pub fn run_status() -> Result<()> {
             )
         })?
         .filter_map(|entry| entry.ok().map(|e| e.path()))
-        .filter(|path| path.extension().and_then(|x| x.to_str()) == Some("json"))
+        .filter(|path| path.is_file() && is_profile_path(path))
+        .collect();
+    // Overlays are reported with the profile they belong to.
+    let overlays: BTreeSet<PathBuf> = profiles
+        .iter()
+        .flat_map(|path| list_overlays(path))
+        .map(|(_, overlay)| overlay)
         .collect();
+    profiles.retain(|path| !overlays.contains(path));
     profiles.sort();
 
     println!("profiles_count: {}", profiles.len());
@@ -55,13 +102,182 @@ pub fn run_status() -> Result<()> {
             .file_name()
             .and_then(|x| x.to_str())
             .unwrap_or("<invalid-utf8>");
-        let status = match crate::core::profile::load(&path) {
-            Ok(_) => "ok".to_string(),
-            Err(err) => format!("invalid: {}", err),
+        let options = LoadOptions {
+            allow_missing_params: true,
+            ..LoadOptions::default()
         };
-        println!("- {} [{}]", name, status);
+        let overlays = describe_overlays(&path);
+        match crate::core::profile::load_with_options(&path, &options) {
+            Ok(profile) => println!(
+                "- {} [ok]{}{}",
+                name,
+                profile.meta.as_ref().map(describe_meta).unwrap_or_default(),
+                overlays
+            ),
+            Err(err) => println!("- {} [invalid: {:#}]{}", name, err, overlays),
+        }
+    }
+
+    Ok(())
+}
+
+fn describe_overlays(path: &Path) -> String {
+    let stages: Vec<String> = list_overlays(path)
+        .into_iter()
+        .map(|(stage, _)| stage)
+        .collect();
+    if stages.is_empty() {
+        return String::new();
+    }
+    format!(" overlays=[{}]", stages.join(", "))
+}
+
+fn describe_meta(meta: &ProfileMeta) -> String {
+    let mut out = String::new();
+    if let Some(name) = &meta.name {
+        out.push_str(&format!(" name=\"{}\"", name));
+    }
+    if let Some(description) = &meta.description {
+        out.push_str(&format!(" description=\"{}\"", description));
+    }
+    if !meta.tags.is_empty() {
+        out.push_str(&format!(" tags=[{}]", meta.tags.join(", ")));
+    }
+    out
+}
+
+pub fn run_migrate(options: ProfilesMigrateOptions) -> Result<()> {
+    let migration = crate::core::profile::migrate(&options.path)?;
+    if migration.is_noop() {
+        println!(
+            "profile already uses schema_version {}: {}",
+            CURRENT_SCHEMA_VERSION,
+            options.path.display()
+        );
+        return Ok(());
+    }
+
+    if options.write {
+        std::fs::write(&options.path, &migration.migrated).with_context(|| {
+            format!(
+                "failed to write migrated profile: {}",
+                options.path.display()
+            )
+        })?;
+        println!(
+            "migrated {} to schema_version {}",
+            options.path.display(),
+            CURRENT_SCHEMA_VERSION
+        );
+    } else {
+        let name = options.path.display().to_string();
+        print!(
+            "{}",
+            TextDiff::from_lines(&migration.original, &migration.migrated)
+                .unified_diff()
+                .header(&name, &name)
+        );
+    }
+    Ok(())
+}
+
+pub fn run_fmt(options: ProfilesFmtOptions) -> Result<()> {
+    let mut unformatted = 0usize;
+    for path in &options.paths {
+        let canonical = canonicalize(path)?;
+        if canonical.is_formatted() {
+            continue;
+        }
+        if canonical.is_lossy() {
+            println!(
+                "skipped: {} (formatting would drop {} comments)",
+                path.display(),
+                canonical.format.name()
+            );
+            continue;
+        }
+        unformatted += 1;
+        if options.check {
+            println!("would reformat: {}", path.display());
+        } else {
+            std::fs::write(path, &canonical.formatted)
+                .with_context(|| format!("failed to write profile file: {}", path.display()))?;
+            println!("formatted: {}", path.display());
+        }
+    }
+    if options.check && unformatted > 0 {
+        bail!(
+            "{} profile file(s) not formatted; run `envlock profiles fmt`",
+            unformatted
+        );
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct LintReport {
+    profile: String,
+    findings: Vec<LintFinding>,
+}
+
+pub fn run_lint(options: ProfilesLintOptions) -> Result<()> {
+    let env = RawEnv::from_process();
+    let envlock_home = resolve_envlock_home(&env)?;
+    let ctx = LintContext {
+        resource_home: resolve_resource_home(&env, &envlock_home),
+        envlock_home: envlock_home.clone(),
+        search_path: std::env::var_os("PATH"),
+    };
+    let paths = if options.paths.is_empty() {
+        vec![resolve_profile_path(&env, &envlock_home).0]
+    } else {
+        options.paths
+    };
+    let load_options = LoadOptions {
+        allow_missing_params: true,
+        ..LoadOptions::default()
+    };
+
+    let mut reports = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let profile = crate::core::profile::load_with_options(path, &load_options)
+            .with_context(|| format!("failed to load profile for lint: {}", path.display()))?;
+        reports.push(LintReport {
+            profile: path.display().to_string(),
+            findings: lint(&profile, &ctx),
+        });
+    }
+
+    match options.output {
+        LintOutputMode::Text => {
+            for report in &reports {
+                for finding in &report.findings {
+                    let origin = finding
+                        .origin
+                        .as_ref()
+                        .map_or_else(|| report.profile.clone(), |o| o.display().to_string());
+                    println!(
+                        "{}: {} {} {}: {}",
+                        origin, finding.code, finding.rule, finding.injection, finding.message
+                    );
+                }
+            }
+        }
+        LintOutputMode::Json => println!("{}", serde_json::to_string_pretty(&reports)?),
     }
 
+    let total: usize = reports.iter().map(|report| report.findings.len()).sum();
+    if total > 0 {
+        bail!("profile lint found {} issue(s)", total);
+    }
+    Ok(())
+}
+
+pub fn run_schema() -> Result<()> {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&crate::core::profile::json_schema())?
+    );
     Ok(())
 }
 
@@ -80,13 +296,30 @@ pub fn run_init(options: ProfilesInitOptions) -> Result<()> {
             if name.trim().is_empty() {
                 bail!("--name cannot be empty");
             }
-            if name.ends_with(".json") {
+            if is_profile_path(Path::new(&name)) {
+                let named = ProfileFormat::from_path(Path::new(&name))?;
+                if let Some(format) = options.format
+                    && format != named
+                {
+                    bail!(
+                        "--name `{name}` is a {} profile but --format is {}",
+                        named.extension(),
+                        format.extension()
+                    );
+                }
                 name
             } else {
-                format!("{}.json", name)
+                format!(
+                    "{}.{}",
+                    name,
+                    options.format.unwrap_or(ProfileFormat::Json).extension()
+                )
             }
         }
-        None => "default.json".to_string(),
+        None => format!(
+            "default.{}",
+            options.format.unwrap_or(ProfileFormat::Json).extension()
+        ),
     };
 
     let target = profiles_dir.join(file_name);
@@ -97,7 +330,8 @@ pub fn run_init(options: ProfilesInitOptions) -> Result<()> {
         );
     }
 
-    let body = render_profile_template(options.profile_type);
+    let format = ProfileFormat::from_path(&target)?;
+    let body = render_profile_template(options.profile_type, format);
     std::fs::write(&target, body)
         .with_context(|| format!("failed to write profile file: {}", target.display()))?;
 
@@ -105,9 +339,10 @@ pub fn run_init(options: ProfilesInitOptions) -> Result<()> {
     Ok(())
 }
 
-fn render_profile_template(profile_type: InitProfileType) -> String {
-    match profile_type {
-        InitProfileType::Minimal => r#"{
+fn render_profile_template(profile_type: InitProfileType, format: ProfileFormat) -> String {
+    match (profile_type, format) {
+        (InitProfileType::Minimal, ProfileFormat::Json) => r#"{
+  "schema_version": 1,
   "injections": [
     {
       "type": "env",
@@ -119,7 +354,24 @@ fn render_profile_template(profile_type: InitProfileType) -> String {
 }
 "#
         .to_string(),
-        InitProfileType::Sample => r#"{
+        (InitProfileType::Minimal, ProfileFormat::Yaml) => r#"schema_version: 1
+injections:
+  - type: env
+    vars:
+      ENVLOCK_PROFILE: default
+"#
+        .to_string(),
+        (InitProfileType::Minimal, ProfileFormat::Toml) => r#"schema_version = 1
+
+[[injections]]
+type = "env"
+
+[injections.vars]
+ENVLOCK_PROFILE = "default"
+"#
+        .to_string(),
+        (InitProfileType::Sample, ProfileFormat::Json) => r#"{
+  "schema_version": 1,
   "injections": [
     {
       "type": "env",
@@ -136,12 +388,50 @@ fn render_profile_template(profile_type: InitProfileType) -> String {
           "key": "PATH",
           "value": "./bin",
           "separator": "os",
-          "dedup": true
+          "dedup": true,
+          "path": true
         }
       ]
     }
   ]
 }
+"#
+        .to_string(),
+        (InitProfileType::Sample, ProfileFormat::Yaml) => r#"schema_version: 1
+injections:
+  - type: env
+    vars:
+      ENVLOCK_PROFILE: sample
+      ENVLOCK_SCOPE: child-only
+  - type: env
+    ops:
+      - op: prepend
+        key: PATH
+        value: ./bin
+        separator: os
+        dedup: true
+        path: true
+"#
+        .to_string(),
+        (InitProfileType::Sample, ProfileFormat::Toml) => r#"schema_version = 1
+
+[[injections]]
+type = "env"
+
+[injections.vars]
+ENVLOCK_PROFILE = "sample"
+ENVLOCK_SCOPE = "child-only"
+
+[[injections]]
+type = "env"
+
+[[injections.ops]]
+op = "prepend"
+key = "PATH"
+value = "./bin"
+separator = "os"
+dedup = true
+path = true
 "#
         .to_string(),
     }
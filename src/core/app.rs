@@ -11,11 +11,12 @@ pub trait EnvReader: Send + Sync {
 pub trait CommandRunner: Send + Sync {
     fn output(&self, program: &str, args: &[String]) -> Result<Output>;
 
+    /// `None` values remove the variable from the child environment.
     fn output_with_env(
         &self,
         program: &str,
         args: &[String],
-        env_overrides: &[(String, String)],
+        env_overrides: &[(String, Option<String>)],
     ) -> Result<Output> {
         let _ = env_overrides;
         self.output(program, args)
@@ -50,11 +51,17 @@ impl CommandRunner for ProcessCommandRunner {
         &self,
         program: &str,
         args: &[String],
-        env_overrides: &[(String, String)],
+        env_overrides: &[(String, Option<String>)],
     ) -> Result<Output> {
-        Command::new(program)
-            .args(args)
-            .envs(env_overrides.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+        let mut command = Command::new(program);
+        command.args(args);
+        for (key, value) in env_overrides {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+        command
             .output()
             .with_context(|| format!("failed to run command: {program}"))
     }
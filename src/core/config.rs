@@ -1,8 +1,13 @@
-use std::path::PathBuf;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 
 use anyhow::{Result, bail};
 use tracing_subscriber::filter::LevelFilter;
 
+use super::profile::{find_profile, validate_stage};
+
 #[derive(Debug, Clone, Copy)]
 pub enum OutputMode {
     Shell,
@@ -17,12 +22,18 @@ pub enum LogFormat {
 
 #[derive(Debug, Clone)]
 pub struct CliInput {
-    pub profile: Option<PathBuf>,
+    /// `--profile` values in order; empty means discovery or the home default.
+    pub profiles: Vec<PathBuf>,
     pub output_mode: OutputMode,
     pub strict: bool,
     pub log_level: LevelFilter,
     pub log_format: LogFormat,
     pub command: Vec<String>,
+    pub params: BTreeMap<String, String>,
+    pub enable_ids: Vec<String>,
+    pub disable_ids: Vec<String>,
+    /// `--stage`; wins over `ENVLOCK_STAGE`.
+    pub stage: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -30,6 +41,9 @@ pub struct RawEnv {
     pub home: Option<PathBuf>,
     pub envlock_home: Option<PathBuf>,
     pub envlock_resource_home: Option<PathBuf>,
+    pub cwd: Option<PathBuf>,
+    pub no_discovery: bool,
+    pub stage: Option<String>,
 }
 
 impl RawEnv {
@@ -37,25 +51,60 @@ impl RawEnv {
         Self {
             home: std::env::var_os("HOME")
                 .map(PathBuf::from)
-                .filter(non_empty_path),
+                .filter(|path| non_empty_path(path)),
             envlock_home: std::env::var_os("ENVLOCK_HOME")
                 .map(PathBuf::from)
-                .filter(non_empty_path),
+                .filter(|path| non_empty_path(path)),
             envlock_resource_home: std::env::var_os("ENVLOCK_RESOURCE_HOME")
                 .map(PathBuf::from)
-                .filter(non_empty_path),
+                .filter(|path| non_empty_path(path)),
+            cwd: std::env::current_dir().ok(),
+            no_discovery: std::env::var("ENVLOCK_NO_DISCOVERY")
+                .is_ok_and(|value| !matches!(value.trim(), "" | "0" | "false")),
+            stage: std::env::var("ENVLOCK_STAGE")
+                .ok()
+                .filter(|value| !value.trim().is_empty()),
+        }
+    }
+}
+
+/// Where the active profile path came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileSource {
+    /// `--profile` or an alias.
+    Explicit,
+    /// `.envlock.*` or `.envlock/profile.*` found above the working directory.
+    Discovered,
+    /// `envlock_home/profiles/default.*`.
+    HomeDefault,
+}
+
+impl ProfileSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Explicit => "explicit",
+            Self::Discovered => "discovered",
+            Self::HomeDefault => "home-default",
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct RuntimeConfig {
-    pub profile_path: PathBuf,
+    /// Profiles stacked into one lifecycle, in order; never empty.
+    pub profile_paths: Vec<PathBuf>,
+    pub profile_source: ProfileSource,
     pub output_mode: OutputMode,
     pub strict: bool,
     pub log_level: LevelFilter,
     pub log_format: LogFormat,
     pub command: Option<Vec<String>>,
+    pub params: BTreeMap<String, String>,
+    /// Injection ids forced on (`--enable`) or off (`--disable`).
+    pub enable_ids: Vec<String>,
+    pub disable_ids: Vec<String>,
+    /// Selects `<stem>.<stage>.<ext>` overlays next to each profile.
+    pub stage: Option<String>,
     pub envlock_home: PathBuf,
     pub resource_home: PathBuf,
 }
@@ -63,27 +112,33 @@ pub struct RuntimeConfig {
 impl RuntimeConfig {
     pub fn from_cli_and_env(cli: CliInput, env: RawEnv) -> Result<Self> {
         let envlock_home = resolve_envlock_home(&env)?;
-        let resource_home = env
-            .envlock_resource_home
-            .filter(non_empty_path)
-            .unwrap_or_else(|| envlock_home.join("resources"));
+        let resource_home = resolve_resource_home(&env, &envlock_home);
 
-        let profile_path = if let Some(profile) = cli.profile {
-            profile
+        let (profile_paths, profile_source) = if cli.profiles.is_empty() {
+            let (path, source) = resolve_profile_path(&env, &envlock_home);
+            (vec![path], source)
         } else {
-            envlock_home.join("profiles/default.json")
+            (cli.profiles, ProfileSource::Explicit)
         };
 
-        if !profile_path.is_file() {
-            bail!(
-                "profile file not found: {}. create default profile at {}/profiles/default.json or pass --profile",
-                profile_path.display(),
-                envlock_home.display()
-            );
+        let stage = cli.stage.or(env.stage);
+        if let Some(stage) = &stage {
+            validate_stage(stage)?;
+        }
+
+        for profile_path in &profile_paths {
+            if !profile_path.is_file() {
+                bail!(
+                    "profile file not found: {}. create default profile at {}/profiles/default.json (or default.yaml/default.toml) or pass --profile",
+                    profile_path.display(),
+                    envlock_home.display()
+                );
+            }
         }
 
         Ok(Self {
-            profile_path,
+            profile_paths,
+            profile_source,
             output_mode: cli.output_mode,
             strict: cli.strict,
             log_level: cli.log_level,
@@ -93,26 +148,83 @@ impl RuntimeConfig {
             } else {
                 Some(cli.command)
             },
+            params: cli.params,
+            enable_ids: cli.enable_ids,
+            disable_ids: cli.disable_ids,
+            stage,
             envlock_home,
             resource_home,
         })
     }
 }
 
+/// `ENVLOCK_RESOURCE_HOME`, falling back to `envlock_home/resources`.
+pub fn resolve_resource_home(env: &RawEnv, envlock_home: &Path) -> PathBuf {
+    env.envlock_resource_home
+        .clone()
+        .filter(|path| non_empty_path(path))
+        .unwrap_or_else(|| envlock_home.join("resources"))
+}
+
+/// Resolves `profiles/default.*`, falling back to `default.json` when none exists.
+pub fn default_profile_path(envlock_home: &Path) -> PathBuf {
+    let profiles_dir = envlock_home.join("profiles");
+    find_profile(&profiles_dir, "default").unwrap_or_else(|| profiles_dir.join("default.json"))
+}
+
+/// Resolves the profile used when `--profile` is not given: a discovered
+/// project profile first, then the home default.
+pub fn resolve_profile_path(env: &RawEnv, envlock_home: &Path) -> (PathBuf, ProfileSource) {
+    match discover_profile(env, envlock_home) {
+        Some(path) => (path, ProfileSource::Discovered),
+        None => (
+            default_profile_path(envlock_home),
+            ProfileSource::HomeDefault,
+        ),
+    }
+}
+
+/// Walks up from the working directory looking for `.envlock.*` or
+/// `.envlock/profile.*`, stopping after `$HOME` or at the filesystem root.
+/// A `.envlock` directory that is `envlock_home` itself is not a project.
+pub fn discover_profile(env: &RawEnv, envlock_home: &Path) -> Option<PathBuf> {
+    if env.no_discovery {
+        return None;
+    }
+    let home = env.home.as_deref().filter(|path| non_empty_path(path));
+    let mut dir = env.cwd.as_deref();
+    while let Some(current) = dir {
+        let project_dir = current.join(".envlock");
+        let found = find_profile(current, ".envlock").or_else(|| {
+            (project_dir != envlock_home)
+                .then(|| find_profile(&project_dir, "profile"))
+                .flatten()
+        });
+        if found.is_some() {
+            return found;
+        }
+        if Some(current) == home {
+            break;
+        }
+        dir = current.parent();
+    }
+    None
+}
+
 pub fn resolve_envlock_home(env: &RawEnv) -> Result<PathBuf> {
     env.envlock_home
         .clone()
-        .filter(non_empty_path)
+        .filter(|path| non_empty_path(path))
         .or_else(|| {
             env.home
                 .clone()
-                .filter(non_empty_path)
+                .filter(|path| non_empty_path(path))
                 .map(|home| home.join(".envlock"))
         })
         .ok_or_else(|| anyhow::anyhow!("HOME is not set; pass --profile or set ENVLOCK_HOME"))
 }
 
-fn non_empty_path(path: &PathBuf) -> bool {
+fn non_empty_path(path: &Path) -> bool {
     !path.as_os_str().is_empty()
 }
 
@@ -123,12 +235,16 @@ mod tests {
 
     fn base_cli() -> CliInput {
         CliInput {
-            profile: None,
+            profiles: Vec::new(),
             output_mode: OutputMode::Shell,
             strict: false,
             log_level: LevelFilter::WARN,
             log_format: LogFormat::Text,
             command: Vec::new(),
+            params: BTreeMap::new(),
+            enable_ids: Vec::new(),
+            disable_ids: Vec::new(),
+            stage: None,
         }
     }
 
@@ -147,17 +263,45 @@ mod tests {
                 home: Some(PathBuf::from("/Users/tester")),
                 envlock_home: Some(envlock_home.clone()),
                 envlock_resource_home: None,
+                cwd: None,
+                no_discovery: false,
+                stage: None,
             },
         )
         .expect("config should build");
 
         assert_eq!(cfg.envlock_home, envlock_home);
         assert_eq!(
-            cfg.profile_path,
-            temp.path().join("envlock-home/profiles/default.json")
+            cfg.profile_paths,
+            vec![temp.path().join("envlock-home/profiles/default.json")]
         );
     }
 
+    #[test]
+    fn default_profile_accepts_yaml() {
+        let temp = TempDir::new().expect("temp dir should be created");
+        let envlock_home = temp.path().join("envlock-home");
+        let profiles = envlock_home.join("profiles");
+        std::fs::create_dir_all(&profiles).expect("profiles dir should be created");
+        std::fs::write(profiles.join("default.yaml"), "injections: []\n")
+            .expect("default profile should be written");
+
+        let cfg = RuntimeConfig::from_cli_and_env(
+            base_cli(),
+            RawEnv {
+                home: Some(PathBuf::from("/Users/tester")),
+                envlock_home: Some(envlock_home.clone()),
+                envlock_resource_home: None,
+                cwd: None,
+                no_discovery: false,
+                stage: None,
+            },
+        )
+        .expect("config should build");
+
+        assert_eq!(cfg.profile_paths, vec![profiles.join("default.yaml")]);
+    }
+
     #[test]
     fn profile_flag_overrides_default_resolution() {
         let temp = TempDir::new().expect("temp dir should be created");
@@ -165,7 +309,7 @@ mod tests {
         std::fs::write(&explicit, "{\"injections\":[]}").expect("profile should be written");
 
         let mut cli = base_cli();
-        cli.profile = Some(explicit.clone());
+        cli.profiles = vec![explicit.clone()];
 
         let cfg = RuntimeConfig::from_cli_and_env(
             cli,
@@ -173,11 +317,14 @@ mod tests {
                 home: Some(PathBuf::from("/Users/tester")),
                 envlock_home: None,
                 envlock_resource_home: None,
+                cwd: None,
+                no_discovery: false,
+                stage: None,
             },
         )
         .expect("config should build");
 
-        assert_eq!(cfg.profile_path, explicit);
+        assert_eq!(cfg.profile_paths, vec![explicit]);
     }
 
     #[test]
@@ -197,6 +344,9 @@ mod tests {
                 home: Some(PathBuf::from("/Users/tester")),
                 envlock_home: Some(envlock_home.clone()),
                 envlock_resource_home: None,
+                cwd: None,
+                no_discovery: false,
+                stage: None,
             },
         )
         .expect("config should build");
@@ -212,6 +362,9 @@ mod tests {
                 home: Some(PathBuf::from("/Users/tester")),
                 envlock_home: Some(PathBuf::from("/tmp/does-not-exist")),
                 envlock_resource_home: None,
+                cwd: None,
+                no_discovery: false,
+                stage: None,
             },
         )
         .expect_err("missing default profile should fail");
@@ -226,6 +379,9 @@ mod tests {
                 home: None,
                 envlock_home: None,
                 envlock_resource_home: None,
+                cwd: None,
+                no_discovery: false,
+                stage: None,
             },
         )
         .expect_err("missing home should fail");
@@ -249,10 +405,118 @@ mod tests {
                 home: Some(home.clone()),
                 envlock_home: Some(PathBuf::new()),
                 envlock_resource_home: None,
+                cwd: None,
+                no_discovery: false,
+                stage: None,
             },
         )
         .expect("config should fall back to HOME/.envlock");
 
         assert_eq!(cfg.envlock_home, home.join(".envlock"));
     }
+
+    #[test]
+    fn project_profile_is_discovered_above_cwd() {
+        let temp = TempDir::new().expect("temp dir should be created");
+        let home = temp.path().join("home");
+        let repo = home.join("repo");
+        let nested = repo.join("crates/app");
+        std::fs::create_dir_all(&nested).expect("nested dir should exist");
+        std::fs::create_dir_all(home.join(".envlock/profiles")).expect("profiles dir should exist");
+        std::fs::write(
+            home.join(".envlock/profiles/default.json"),
+            "{\"injections\":[]}",
+        )
+        .expect("default profile should be written");
+        std::fs::write(repo.join(".envlock.yaml"), "injections: []\n")
+            .expect("project profile should be written");
+
+        let env = RawEnv {
+            home: Some(home.clone()),
+            envlock_home: None,
+            envlock_resource_home: None,
+            cwd: Some(nested.clone()),
+            no_discovery: false,
+            stage: None,
+        };
+        let cfg =
+            RuntimeConfig::from_cli_and_env(base_cli(), env.clone()).expect("config should build");
+        assert_eq!(cfg.profile_paths, vec![repo.join(".envlock.yaml")]);
+        assert_eq!(cfg.profile_source, ProfileSource::Discovered);
+
+        let cfg = RuntimeConfig::from_cli_and_env(
+            base_cli(),
+            RawEnv {
+                no_discovery: true,
+                ..env
+            },
+        )
+        .expect("config should build");
+        assert_eq!(
+            cfg.profile_paths,
+            vec![home.join(".envlock/profiles/default.json")]
+        );
+        assert_eq!(cfg.profile_source, ProfileSource::HomeDefault);
+    }
+
+    #[test]
+    fn discovery_accepts_dot_envlock_dir_and_stops_at_home() {
+        let temp = TempDir::new().expect("temp dir should be created");
+        let home = temp.path().join("home");
+        let repo = home.join("repo");
+        std::fs::create_dir_all(repo.join(".envlock")).expect("project dir should exist");
+        std::fs::write(repo.join(".envlock/profile.json"), "{\"injections\":[]}")
+            .expect("project profile should be written");
+        std::fs::write(temp.path().join(".envlock.json"), "{\"injections\":[]}")
+            .expect("outside profile should be written");
+
+        let env = RawEnv {
+            home: Some(home.clone()),
+            envlock_home: None,
+            envlock_resource_home: None,
+            cwd: Some(repo.clone()),
+            no_discovery: false,
+            stage: None,
+        };
+        let envlock_home = home.join(".envlock");
+        assert_eq!(
+            discover_profile(&env, &envlock_home),
+            Some(repo.join(".envlock/profile.json"))
+        );
+        assert_eq!(
+            discover_profile(
+                &RawEnv {
+                    cwd: Some(home.clone()),
+                    ..env.clone()
+                },
+                &envlock_home
+            ),
+            None
+        );
+
+        // `$HOME/.envlock/profile.json` belongs to the home, not to a project.
+        std::fs::create_dir_all(&envlock_home).expect("envlock home should exist");
+        std::fs::write(envlock_home.join("profile.json"), "{\"injections\":[]}")
+            .expect("home profile should be written");
+        assert_eq!(
+            discover_profile(
+                &RawEnv {
+                    cwd: Some(home.join("elsewhere")),
+                    ..env.clone()
+                },
+                &envlock_home
+            ),
+            None
+        );
+        assert_eq!(
+            discover_profile(
+                &RawEnv {
+                    cwd: Some(home.clone()),
+                    ..env
+                },
+                &temp.path().join("custom-home")
+            ),
+            Some(envlock_home.join("profile.json"))
+        );
+    }
 }
@@ -0,0 +1,44 @@
+/// Matches `text` against a shell-style pattern supporting `*` and `?`.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0usize, 0usize);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_wildcards() {
+        assert!(glob_match("build-*", "build-01"));
+        assert!(glob_match("ci-??", "ci-42"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("/opt/*/bin", "/opt/conda/bin"));
+        assert!(!glob_match("build-*", "laptop"));
+        assert!(!glob_match("ci-?", "ci-42"));
+    }
+}
@@ -1,6 +1,8 @@
 use anyhow::{Context, Result, bail};
 use std::collections::BTreeMap;
 
+use super::placeholders::Placeholders;
+use super::{Export, OverlayEnv};
 use crate::core::app::{AppContext, EnvReader};
 use crate::core::env_key::is_valid_env_key;
 use crate::core::profile::CommandProfile;
@@ -14,15 +16,20 @@ impl CommandInjection {
         Self { cfg }
     }
 
+    pub(crate) fn id(&self) -> Option<&str> {
+        self.cfg.id.as_deref()
+    }
+
     pub(crate) fn name(&self) -> &'static str {
         "command"
     }
 
-    pub(crate) fn validate(&self) -> Result<()> {
+    pub(crate) fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
         if self.cfg.program.trim().is_empty() {
-            bail!("program must not be empty");
+            problems.push("program must not be empty".to_string());
         }
-        Ok(())
+        problems
     }
 
     pub(crate) fn register(&mut self) -> Result<()> {
@@ -32,15 +39,22 @@ impl CommandInjection {
     pub(crate) fn export(
         &self,
         app: &dyn AppContext,
-        inherited: &BTreeMap<String, String>,
-    ) -> Result<Vec<(String, String)>> {
-        let inherited_pairs: Vec<(String, String)> = inherited
+        inherited: &BTreeMap<String, Option<String>>,
+    ) -> Result<Vec<Export>> {
+        let inherited_pairs: Vec<Export> = inherited
             .iter()
             .map(|(k, v)| (k.clone(), v.clone()))
             .collect();
+        let placeholders = Placeholders::new(app, self.cfg.origin.as_deref());
+        let args: Vec<String> = self
+            .cfg
+            .args
+            .iter()
+            .map(|arg| placeholders.expand(arg))
+            .collect();
         let output = app
             .command_runner()
-            .output_with_env(&self.cfg.program, &self.cfg.args, &inherited_pairs)
+            .output_with_env(&self.cfg.program, &args, &inherited_pairs)
             .with_context(|| format!("failed to run command: {}", self.cfg.program))?;
 
         if !output.status.success() {
@@ -66,13 +80,20 @@ impl CommandInjection {
     }
 }
 
-fn parse_exports(stdout: &str, env: &dyn EnvReader) -> Vec<(String, String)> {
+fn parse_exports(stdout: &str, env: &dyn EnvReader) -> Vec<Export> {
     let mut out = Vec::new();
     for line in stdout.lines() {
         let trimmed = line.trim();
         if trimmed.is_empty() {
             continue;
         }
+        if let Some(key) = trimmed.strip_prefix("unset ") {
+            let key = key.trim();
+            if is_valid_env_key(key) {
+                out.push((key.to_string(), None));
+            }
+            continue;
+        }
         let assignment = trimmed.strip_prefix("export ").unwrap_or(trimmed);
         let Some((key_raw, value_raw)) = assignment.split_once('=') else {
             continue;
@@ -82,7 +103,7 @@ fn parse_exports(stdout: &str, env: &dyn EnvReader) -> Vec<(String, String)> {
             continue;
         }
         let value = normalize_value(value_raw.trim(), env);
-        out.push((key.to_string(), value));
+        out.push((key.to_string(), Some(value)));
     }
     out
 }
@@ -110,29 +131,6 @@ fn strip_quote_delimiters(raw: &str) -> String {
     out
 }
 
-struct OverlayEnv<'a> {
-    base: &'a dyn EnvReader,
-    overlay: BTreeMap<String, String>,
-}
-
-impl<'a> OverlayEnv<'a> {
-    fn new(base: &'a dyn EnvReader, inherited: &BTreeMap<String, String>) -> Self {
-        Self {
-            base,
-            overlay: inherited.clone(),
-        }
-    }
-}
-
-impl EnvReader for OverlayEnv<'_> {
-    fn var(&self, key: &str) -> Option<String> {
-        self.overlay
-            .get(key)
-            .cloned()
-            .or_else(|| self.base.var(key))
-    }
-}
-
 fn expand_vars(input: &str, env: &dyn EnvReader) -> String {
     let mut out = String::new();
     let chars: Vec<char> = input.chars().collect();
@@ -201,8 +199,8 @@ mod tests {
         assert_eq!(
             vars,
             vec![
-                ("A".to_string(), "1".to_string()),
-                ("B".to_string(), "2".to_string())
+                ("A".to_string(), Some("1".to_string())),
+                ("B".to_string(), Some("2".to_string()))
             ]
         );
     }
@@ -218,7 +216,10 @@ mod tests {
         );
         assert_eq!(
             vars,
-            vec![("PATH".to_string(), "/tmp/fnm/bin:/usr/bin:/bin".to_string())]
+            vec![(
+                "PATH".to_string(),
+                Some("/tmp/fnm/bin:/usr/bin:/bin".to_string())
+            )]
         );
     }
 
@@ -228,7 +229,19 @@ mod tests {
             vars: BTreeMap::new(),
         };
         let vars = parse_exports("export A='x\"y\"z'\n", &env);
-        assert_eq!(vars, vec![("A".to_string(), "x\"y\"z".to_string())]);
+        assert_eq!(vars, vec![("A".to_string(), Some("x\"y\"z".to_string()))]);
+    }
+
+    #[test]
+    fn malformed_reference_does_not_block_other_expansions() {
+        let env = MockEnv {
+            vars: BTreeMap::from([("ENVLOCK_TEST_DIR".to_string(), "/opt".to_string())]),
+        };
+        let vars = parse_exports("export A=\"$ENVLOCK_TEST_DIR/bin:${BROKEN:$$\"\n", &env);
+        assert_eq!(
+            vars,
+            vec![("A".to_string(), Some("/opt/bin:${BROKEN:$$".to_string()))]
+        );
     }
 
     #[test]
@@ -237,6 +250,6 @@ mod tests {
             vars: BTreeMap::new(),
         };
         let vars = parse_exports("export BAD-KEY=1\nexport _GOOD=2\n", &env);
-        assert_eq!(vars, vec![("_GOOD".to_string(), "2".to_string())]);
+        assert_eq!(vars, vec![("_GOOD".to_string(), Some("2".to_string()))]);
     }
 }
@@ -1,9 +1,19 @@
 use std::collections::BTreeMap;
-use std::path::Path;
+use std::ops::Range;
+use std::path::{Component, Path};
 
 use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use percent_encoding::percent_decode_str;
+use regex::Regex;
 
-use crate::core::app::AppContext;
+use super::interpolate::{Expander, Piece};
+use super::placeholders::{Placeholders, absolutize_segments};
+use super::{Export, OverlayEnv};
+use crate::core::app::{AppContext, EnvReader};
+use crate::core::env_key::is_valid_env_key;
+use crate::core::glob::glob_match;
 use crate::core::profile::{EnvOpProfile, EnvProfile};
 
 pub(crate) struct EnvInjection {
@@ -15,20 +25,30 @@ impl EnvInjection {
         Self { cfg }
     }
 
+    pub(crate) fn id(&self) -> Option<&str> {
+        self.cfg.id.as_deref()
+    }
+
     pub(crate) fn name(&self) -> &'static str {
         "env"
     }
 
-    pub(crate) fn validate(&self) -> Result<()> {
+    /// Returns every problem in the block, so one report covers them all.
+    pub(crate) fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
         for key in self.cfg.vars.keys() {
-            if key.trim().is_empty() {
-                bail!("env var key must not be empty");
-            }
+            check_key(key, &mut problems);
         }
+        check_separator(&self.cfg.separator, &mut problems);
         for op in &self.cfg.ops {
             match op {
                 EnvOpProfile::Set { key, value } | EnvOpProfile::SetIfAbsent { key, value } => {
-                    validate_key_value(key, value)?
+                    check_key(key, &mut problems);
+                    if !self.cfg.allow_empty && value.trim().is_empty() {
+                        problems.push(format!(
+                            "env var value for `{key}` must not be empty; set `allow_empty` or use `set_empty`"
+                        ));
+                    }
                 }
                 EnvOpProfile::Prepend {
                     key,
@@ -41,37 +61,75 @@ impl EnvInjection {
                     value,
                     separator,
                     ..
+                }
+                | EnvOpProfile::Remove {
+                    key,
+                    value,
+                    separator,
                 } => {
-                    validate_key_value(key, value)?;
-                    if let Some(sep) = separator {
-                        if sep != "os" && sep.is_empty() {
-                            bail!("separator must not be empty");
-                        }
+                    check_key(key, &mut problems);
+                    check_value(key, value, &mut problems);
+                    check_separator(separator, &mut problems);
+                }
+                EnvOpProfile::Normalize { key, separator } => {
+                    check_key(key, &mut problems);
+                    check_separator(separator, &mut problems);
+                }
+                EnvOpProfile::Copy { from, to } | EnvOpProfile::Move { from, to } => {
+                    check_key(from, &mut problems);
+                    check_key(to, &mut problems);
+                    if from == to {
+                        problems.push(format!("`from` and `to` must differ, both are `{from}`"));
                     }
                 }
-                EnvOpProfile::Unset { key } => {
-                    if key.trim().is_empty() {
-                        bail!("env var key must not be empty");
+                EnvOpProfile::Replace { key, pattern, .. } => {
+                    check_key(key, &mut problems);
+                    if let Err(err) = Regex::new(pattern) {
+                        problems.push(format!("invalid replace pattern for `{key}`: {err}"));
                     }
                 }
+                EnvOpProfile::SetEmpty { key } | EnvOpProfile::Unset { key } => {
+                    check_key(key, &mut problems);
+                }
             }
         }
-        Ok(())
+        problems
     }
 
     pub(crate) fn register(&mut self) -> Result<()> {
         Ok(())
     }
 
-    pub(crate) fn export(&self, app: &dyn AppContext) -> Result<Vec<(String, String)>> {
-        let resource_home = &app.config().resource_home;
-        let mut env: BTreeMap<String, String> = self
+    /// `inherited` holds exports of earlier injections; ops read it before
+    /// falling back to the process environment.
+    pub(crate) fn export(
+        &self,
+        app: &dyn AppContext,
+        inherited: &BTreeMap<String, Option<String>>,
+    ) -> Result<Vec<Export>> {
+        let config = app.config();
+        let resolver = ValueResolver {
+            placeholders: Placeholders::new(app, self.cfg.origin.as_deref()),
+            resource_home: &config.resource_home,
+            interpolate: self.cfg.interpolate,
+            strict: config.strict,
+            allow_resource_escape: self.cfg.allow_resource_escape,
+        };
+        let base = OverlayEnv::new(app.env(), inherited);
+        let separator = separator_value(&self.cfg.separator);
+        // `vars` is unordered, so it only sees earlier injections.
+        let mut env: BTreeMap<String, Option<String>> = self
             .cfg
             .vars
             .iter()
-            .map(|(k, v)| Ok((k.clone(), resolve_resource_refs(v, resource_home)?)))
+            .map(|(k, v)| {
+                let value = resolver
+                    .resolve(&v.to_env_string(separator), &base)
+                    .with_context(|| format!("failed to resolve env var `{k}`"))?;
+                Ok((k.clone(), Some(value)))
+            })
             .collect::<Result<_>>()?;
-        apply_ops(app, &mut env, &self.cfg.ops, resource_home)?;
+        apply_ops(&base, &mut env, &self.cfg.ops, &resolver)?;
         Ok(env.into_iter().collect())
     }
 
@@ -80,30 +138,98 @@ impl EnvInjection {
     }
 }
 
-fn validate_key_value(key: &str, value: &str) -> Result<()> {
+fn check_key(key: &str, problems: &mut Vec<String>) {
     if key.trim().is_empty() {
-        bail!("env var key must not be empty");
+        problems.push("env var key must not be empty".to_string());
+    } else if !is_valid_env_key(key) {
+        problems.push(format!("invalid env var key `{key}`"));
     }
+}
+
+fn check_value(key: &str, value: &str, problems: &mut Vec<String>) {
     if value.trim().is_empty() {
-        bail!("env var value must not be empty");
+        problems.push(format!("env var value for `{key}` must not be empty"));
+    }
+}
+
+fn check_separator(separator: &Option<String>, problems: &mut Vec<String>) {
+    if let Some(sep) = separator
+        && sep != "os"
+        && sep.is_empty()
+    {
+        problems.push("separator must not be empty".to_string());
+    }
+}
+
+/// Expands built-in placeholders, and variables when `interpolate` is on, then
+/// resolves the `resource://` references written in the profile.
+struct ValueResolver<'a> {
+    placeholders: Placeholders,
+    resource_home: &'a Path,
+    interpolate: bool,
+    strict: bool,
+    allow_resource_escape: bool,
+}
+
+impl ValueResolver<'_> {
+    fn resolve(&self, value: &str, env: &dyn EnvReader) -> Result<String> {
+        let expander = Expander {
+            env: self.interpolate.then_some(env),
+            placeholders: Some(&self.placeholders),
+            strict: self.strict,
+        };
+        let mut resolved = String::new();
+        for piece in expander.pieces(value)? {
+            match piece {
+                Piece::Literal(text) => resolved.push_str(&resolve_resource_refs(
+                    &text,
+                    self.resource_home,
+                    self.allow_resource_escape,
+                )?),
+                // Substituted values are data; a URI inside one is not resolved.
+                Piece::Substituted(value) => resolved.push_str(&value),
+            }
+        }
+        Ok(resolved)
+    }
+
+    fn resolve_list(
+        &self,
+        value: &str,
+        env: &dyn EnvReader,
+        separator: &str,
+        path: bool,
+    ) -> Result<String> {
+        let resolved = self.resolve(value, env)?;
+        if path {
+            return Ok(absolutize_segments(
+                &resolved,
+                separator,
+                self.placeholders.profile_dir(),
+            ));
+        }
+        Ok(resolved)
     }
-    Ok(())
 }
 
 fn apply_ops(
-    app: &dyn AppContext,
-    env: &mut BTreeMap<String, String>,
+    base: &dyn EnvReader,
+    env: &mut BTreeMap<String, Option<String>>,
     ops: &[EnvOpProfile],
-    resource_home: &Path,
+    resolver: &ValueResolver,
 ) -> Result<()> {
     for op in ops {
+        // Ops see the block's earlier writes on top of `base`.
+        let current = OverlayEnv::new(base, env);
         match op {
             EnvOpProfile::Set { key, value } => {
-                env.insert(key.clone(), resolve_resource_refs(value, resource_home)?);
+                let resolved = resolve_op(resolver, &current, key, value)?;
+                env.insert(key.clone(), Some(resolved));
             }
             EnvOpProfile::SetIfAbsent { key, value } => {
-                if !env.contains_key(key) && app.env().var(key).is_none() {
-                    env.insert(key.clone(), resolve_resource_refs(value, resource_home)?);
+                if current_value(base, env, key).is_none() {
+                    let resolved = resolve_op(resolver, &current, key, value)?;
+                    env.insert(key.clone(), Some(resolved));
                 }
             }
             EnvOpProfile::Prepend {
@@ -111,63 +237,122 @@ fn apply_ops(
                 value,
                 separator,
                 dedup,
+                path,
             } => {
-                let merged =
-                    merge_env_op(app, env, key, value, separator, *dedup, true, resource_home)?;
-                env.insert(key.clone(), merged);
+                let sep = separator_value(separator);
+                let resolved = resolver
+                    .resolve_list(value, &current, sep, *path)
+                    .with_context(|| format!("failed to resolve env op for `{key}`"))?;
+                let merged = merge_env_op(base, env, key, &resolved, sep, *dedup, true);
+                env.insert(key.clone(), Some(merged));
             }
             EnvOpProfile::Append {
                 key,
                 value,
                 separator,
                 dedup,
+                path,
             } => {
-                let merged = merge_env_op(
-                    app,
-                    env,
-                    key,
-                    value,
-                    separator,
-                    *dedup,
-                    false,
-                    resource_home,
-                )?;
-                env.insert(key.clone(), merged);
+                let sep = separator_value(separator);
+                let resolved = resolver
+                    .resolve_list(value, &current, sep, *path)
+                    .with_context(|| format!("failed to resolve env op for `{key}`"))?;
+                let merged = merge_env_op(base, env, key, &resolved, sep, *dedup, false);
+                env.insert(key.clone(), Some(merged));
+            }
+            EnvOpProfile::Remove {
+                key,
+                value,
+                separator,
+            } => {
+                let sep = separator_value(separator);
+                let patterns = resolve_op(resolver, &current, key, value)?;
+                if let Some(existing) = current_value(base, env, key) {
+                    env.insert(key.clone(), Some(remove_parts(&existing, &patterns, sep)));
+                }
+            }
+            EnvOpProfile::Normalize { key, separator } => {
+                let sep = separator_value(separator);
+                if let Some(existing) = current_value(base, env, key) {
+                    env.insert(key.clone(), Some(normalize_parts(&existing, sep)));
+                }
+            }
+            EnvOpProfile::Copy { from, to } => {
+                if let Some(value) = current_value(base, env, from) {
+                    env.insert(to.clone(), Some(value));
+                }
+            }
+            EnvOpProfile::Move { from, to } => {
+                if let Some(value) = current_value(base, env, from) {
+                    env.insert(to.clone(), Some(value));
+                    env.insert(from.clone(), None);
+                }
+            }
+            EnvOpProfile::Replace {
+                key,
+                pattern,
+                replacement,
+            } => {
+                if let Some(existing) = current_value(base, env, key) {
+                    let regex = Regex::new(pattern)
+                        .with_context(|| format!("invalid replace pattern for `{key}`"))?;
+                    let replaced = regex.replace_all(&existing, replacement.as_str());
+                    env.insert(key.clone(), Some(replaced.into_owned()));
+                }
+            }
+            EnvOpProfile::SetEmpty { key } => {
+                env.insert(key.clone(), Some(String::new()));
             }
             EnvOpProfile::Unset { key } => {
-                env.remove(key);
+                env.insert(key.clone(), None);
             }
         }
     }
     Ok(())
 }
 
-fn merge_env_op(
-    app: &dyn AppContext,
-    env: &BTreeMap<String, String>,
+fn resolve_op(
+    resolver: &ValueResolver,
+    env: &dyn EnvReader,
     key: &str,
     value: &str,
-    separator: &Option<String>,
+) -> Result<String> {
+    resolver
+        .resolve(value, env)
+        .with_context(|| format!("failed to resolve env op for `{key}`"))
+}
+
+/// The block's own write wins, including an unset; otherwise earlier exports
+/// and the process environment.
+fn current_value(
+    base: &dyn EnvReader,
+    env: &BTreeMap<String, Option<String>>,
+    key: &str,
+) -> Option<String> {
+    match env.get(key) {
+        Some(value) => value.clone(),
+        None => base.var(key),
+    }
+}
+
+fn merge_env_op(
+    base_env: &dyn EnvReader,
+    env: &BTreeMap<String, Option<String>>,
+    key: &str,
+    resolved: &str,
+    sep: &str,
     dedup: bool,
     prepend: bool,
-    resource_home: &Path,
-) -> Result<String> {
-    let sep = separator_value(separator);
-    let base = env
-        .get(key)
-        .cloned()
-        .or_else(|| app.env().var(key))
-        .unwrap_or_default();
-    let resolved = resolve_resource_refs(value, resource_home)?;
-    let merged = if prepend {
-        merge_values(&resolved, &base, sep, dedup)
+) -> String {
+    let base = current_value(base_env, env, key).unwrap_or_default();
+    if prepend {
+        merge_values(resolved, &base, sep, dedup)
     } else {
-        merge_values(&base, &resolved, sep, dedup)
-    };
-    Ok(merged)
+        merge_values(&base, resolved, sep, dedup)
+    }
 }
 
-fn separator_value(separator: &Option<String>) -> &str {
+pub(crate) fn separator_value(separator: &Option<String>) -> &str {
     match separator.as_deref() {
         None | Some("os") => {
             if cfg!(windows) {
@@ -200,6 +385,32 @@ fn merge_values(left: &str, right: &str, separator: &str, dedup: bool) -> String
     out.join(separator)
 }
 
+/// Drops segments of `value` matching any segment of `patterns`.
+fn remove_parts(value: &str, patterns: &str, separator: &str) -> String {
+    let patterns = split_parts(patterns, separator);
+    split_parts(value, separator)
+        .into_iter()
+        .filter(|part| !patterns.iter().any(|pattern| glob_match(pattern, part)))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Replaces each segment with its real path, skipping missing paths and
+/// repeats of an earlier real path.
+fn normalize_parts(value: &str, separator: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    for part in split_parts(value, separator) {
+        let Ok(real) = std::fs::canonicalize(&part) else {
+            continue;
+        };
+        let real = real.to_string_lossy().to_string();
+        if !out.contains(&real) {
+            out.push(real);
+        }
+    }
+    out.join(separator)
+}
+
 fn split_parts(value: &str, separator: &str) -> Vec<String> {
     value
         .split(separator)
@@ -210,56 +421,199 @@ fn split_parts(value: &str, separator: &str) -> Vec<String> {
 
 const RESOURCE_URI_PREFIX: &str = "resource://";
 const RESOURCE_CONTENT_URI_PREFIX: &str = "resource-content://";
+const RESOURCE_CONTENT_TRIM_URI_PREFIX: &str = "resource-content-trim://";
+const RESOURCE_BASE64_URI_PREFIX: &str = "resource-base64://";
+const RESOURCE_JSON_URI_PREFIX: &str = "resource-json://";
 
-fn resolve_resource_refs(value: &str, resource_home: &Path) -> Result<String> {
+const RESOURCE_URI_PREFIXES: [&str; 5] = [
+    RESOURCE_URI_PREFIX,
+    RESOURCE_CONTENT_URI_PREFIX,
+    RESOURCE_CONTENT_TRIM_URI_PREFIX,
+    RESOURCE_BASE64_URI_PREFIX,
+    RESOURCE_JSON_URI_PREFIX,
+];
+
+/// One resource reference found in an env value.
+pub(crate) struct ResourceRef {
+    /// Byte range of the whole URI in the scanned value.
+    span: Range<usize>,
+    prefix: &'static str,
+    /// Path relative to the resource home, percent-decoded unless braced.
+    pub(crate) rel: String,
+    /// JSON Pointer of a `resource-json://` reference.
+    pointer: String,
+}
+
+impl ResourceRef {
+    /// The URI as written in `value`, the string it was scanned from.
+    pub(crate) fn uri<'a>(&self, value: &'a str) -> &'a str {
+        &value[self.span.clone()]
+    }
+}
+
+fn resolve_resource_refs(value: &str, resource_home: &Path, allow_escape: bool) -> Result<String> {
     let mut out = String::new();
-    let mut rest = value;
-
-    while let Some((idx, prefix)) = find_next_resource_prefix(rest) {
-        out.push_str(&rest[..idx]);
-        let token_start = idx + prefix.len();
-        let after = &rest[token_start..];
-        let token_end = after
-            .char_indices()
-            .find(|(_, c)| is_resource_token_delimiter(*c))
-            .map(|(i, _)| i)
-            .unwrap_or(after.len());
-        let rel = &after[..token_end];
-        if rel.is_empty() {
-            out.push_str(prefix);
-        } else {
-            let abs = resource_home.join(rel);
-            if prefix == RESOURCE_CONTENT_URI_PREFIX {
-                let content = std::fs::read_to_string(&abs).with_context(|| {
-                    format!("failed to read resource content: {}", abs.display())
-                })?;
-                out.push_str(&content);
-            } else {
-                out.push_str(&abs.to_string_lossy());
+    let mut last = 0;
+    for reference in scan_resource_refs(value)? {
+        out.push_str(&value[last..reference.span.start]);
+        out.push_str(&resolve_resource(
+            &reference,
+            reference.uri(value),
+            resource_home,
+            allow_escape,
+        )?);
+        last = reference.span.end;
+    }
+    out.push_str(&value[last..]);
+    Ok(out)
+}
+
+/// Resolves one reference; errors name the URI and the absolute path it
+/// points at.
+fn resolve_resource(
+    reference: &ResourceRef,
+    uri: &str,
+    resource_home: &Path,
+    allow_escape: bool,
+) -> Result<String> {
+    let abs = resource_home.join(&reference.rel);
+    if !allow_escape && escapes_resource_home(&reference.rel) {
+        bail!(
+            "resource URI {uri} escapes the resource home: {}; set `allow_resource_escape` to permit it",
+            abs.display()
+        );
+    }
+    match reference.prefix {
+        RESOURCE_URI_PREFIX => return Ok(abs.to_string_lossy().to_string()),
+        RESOURCE_BASE64_URI_PREFIX => {
+            let bytes = std::fs::read(&abs).with_context(|| {
+                format!(
+                    "failed to read resource content for {uri}: {}",
+                    abs.display()
+                )
+            })?;
+            return Ok(BASE64_STANDARD.encode(bytes));
+        }
+        _ => {}
+    }
+    let content = std::fs::read_to_string(&abs).with_context(|| {
+        format!(
+            "failed to read resource content for {uri}: {}",
+            abs.display()
+        )
+    })?;
+    match reference.prefix {
+        RESOURCE_CONTENT_TRIM_URI_PREFIX => Ok(content.trim_end_matches(['\r', '\n']).to_string()),
+        RESOURCE_JSON_URI_PREFIX => {
+            let document: serde_json::Value = serde_json::from_str(&content)
+                .with_context(|| format!("failed to parse JSON for {uri}: {}", abs.display()))?;
+            match document.pointer(&reference.pointer) {
+                Some(serde_json::Value::String(value)) => Ok(value.clone()),
+                Some(value) => Ok(value.to_string()),
+                None => bail!(
+                    "JSON pointer `{}` not found for {uri}: {}",
+                    reference.pointer,
+                    abs.display()
+                ),
             }
         }
-        rest = &after[token_end..];
+        _ => Ok(content),
     }
-    out.push_str(rest);
-    Ok(out)
 }
 
-fn find_next_resource_prefix(input: &str) -> Option<(usize, &'static str)> {
-    let file_idx = input.find(RESOURCE_URI_PREFIX);
-    let content_idx = input.find(RESOURCE_CONTENT_URI_PREFIX);
+/// True when `rel` is absolute or climbs above its base through `..`.
+fn escapes_resource_home(rel: &str) -> bool {
+    let mut depth = 0usize;
+    for component in Path::new(rel).components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(parent) => depth = parent,
+                None => return true,
+            },
+            Component::RootDir | Component::Prefix(_) => return true,
+        }
+    }
+    false
+}
 
-    match (file_idx, content_idx) {
-        (Some(a), Some(b)) => {
-            if a <= b {
-                Some((a, RESOURCE_URI_PREFIX))
-            } else {
-                Some((b, RESOURCE_CONTENT_URI_PREFIX))
+fn scan_resource_refs(value: &str) -> Result<Vec<ResourceRef>> {
+    let mut refs = Vec::new();
+    scan_resource_refs_into(value, &mut refs)?;
+    Ok(refs)
+}
+
+/// Finds every resource reference in `value`. A token is either
+/// `{...}`, taken literally up to the closing brace, or runs to the next
+/// delimiter and is percent-decoded. On error, `refs` keeps the references
+/// before the malformed one.
+fn scan_resource_refs_into(value: &str, refs: &mut Vec<ResourceRef>) -> Result<()> {
+    let mut offset = 0;
+    while let Some((idx, prefix)) = find_next_resource_prefix(&value[offset..]) {
+        let start = offset + idx;
+        let token_start = start + prefix.len();
+        let after = &value[token_start..];
+        let (token, token_len, braced) = match after.strip_prefix('{') {
+            Some(inner) => {
+                let Some(close) = inner.find('}') else {
+                    bail!("unterminated `{{` in resource URI: {}", &value[start..]);
+                };
+                (&inner[..close], close + 2, true)
             }
+            None => {
+                let end = after
+                    .find(is_resource_token_delimiter)
+                    .unwrap_or(after.len());
+                (&after[..end], end, false)
+            }
+        };
+        offset = token_start + token_len;
+        if token.is_empty() {
+            continue;
         }
-        (Some(a), None) => Some((a, RESOURCE_URI_PREFIX)),
-        (None, Some(b)) => Some((b, RESOURCE_CONTENT_URI_PREFIX)),
-        (None, None) => None,
+        let (rel, pointer) = match prefix {
+            RESOURCE_JSON_URI_PREFIX => token.split_once('#').unwrap_or((token, "")),
+            _ => (token, ""),
+        };
+        let decode = |part: &str| -> Result<String> {
+            if braced {
+                return Ok(part.to_string());
+            }
+            percent_decode_str(part)
+                .decode_utf8()
+                .map(|decoded| decoded.into_owned())
+                .with_context(|| {
+                    format!(
+                        "invalid percent-encoding in resource URI: {}",
+                        &value[start..offset]
+                    )
+                })
+        };
+        refs.push(ResourceRef {
+            span: start..offset,
+            prefix,
+            rel: decode(rel)?,
+            pointer: decode(pointer)?,
+        });
     }
+    Ok(())
+}
+
+/// Resource references in `value`, in order, up to the first malformed one,
+/// whose error comes back alongside.
+pub(crate) fn resource_references(value: &str) -> (Vec<ResourceRef>, Option<anyhow::Error>) {
+    let mut refs = Vec::new();
+    let error = scan_resource_refs_into(value, &mut refs).err();
+    (refs, error)
+}
+
+/// The earliest resource prefix in `input`.
+fn find_next_resource_prefix(input: &str) -> Option<(usize, &'static str)> {
+    RESOURCE_URI_PREFIXES
+        .iter()
+        .filter_map(|prefix| input.find(prefix).map(|idx| (idx, *prefix)))
+        .min_by_key(|(idx, _)| *idx)
 }
 
 fn is_resource_token_delimiter(c: char) -> bool {
@@ -276,7 +630,8 @@ mod tests {
 
     use super::*;
     use crate::core::app::{AppContext, CommandRunner, EnvReader};
-    use crate::core::config::{LogFormat, OutputMode, RuntimeConfig};
+    use crate::core::config::{LogFormat, OutputMode, ProfileSource, RuntimeConfig};
+    use crate::core::injections::tests::env_profile;
     use tracing_subscriber::filter::LevelFilter;
 
     struct TestEnv {
@@ -310,12 +665,17 @@ mod tests {
         fn new(resource_home: &str, vars: BTreeMap<String, String>) -> Self {
             Self {
                 cfg: RuntimeConfig {
-                    profile_path: PathBuf::from("/tmp/unused.json"),
+                    profile_paths: vec![PathBuf::from("/tmp/unused.json")],
+                    profile_source: ProfileSource::Explicit,
                     output_mode: OutputMode::Shell,
                     strict: false,
                     log_level: LevelFilter::WARN,
                     log_format: LogFormat::Text,
                     command: None,
+                    params: BTreeMap::new(),
+                    enable_ids: Vec::new(),
+                    disable_ids: Vec::new(),
+                    stage: None,
                     envlock_home: PathBuf::from("/tmp/envlock-home"),
                     resource_home: PathBuf::from(resource_home),
                 },
@@ -342,39 +702,87 @@ mod tests {
     #[test]
     fn rejects_empty_env_key() {
         let mut vars = BTreeMap::new();
-        vars.insert("   ".to_string(), "x".to_string());
-        let injection = EnvInjection::new(EnvProfile {
-            enabled: true,
-            vars,
-            ops: Vec::new(),
-        });
-        let err = injection.validate().expect_err("empty key should fail");
-        assert!(err.to_string().contains("env var key must not be empty"));
+        vars.insert("   ".to_string(), "x".into());
+        let injection = EnvInjection::new(env_profile(vars, Vec::new()));
+        assert_eq!(injection.validate(), vec!["env var key must not be empty"]);
+    }
+
+    #[test]
+    fn rejects_copy_and_move_onto_the_source_key() {
+        let injection = EnvInjection::new(env_profile(
+            BTreeMap::new(),
+            vec![
+                EnvOpProfile::Copy {
+                    from: "A".to_string(),
+                    to: "A".to_string(),
+                },
+                EnvOpProfile::Move {
+                    from: "B".to_string(),
+                    to: "B".to_string(),
+                },
+            ],
+        ));
+        assert_eq!(
+            injection.validate(),
+            vec![
+                "`from` and `to` must differ, both are `A`",
+                "`from` and `to` must differ, both are `B`",
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_value_hint_is_only_given_where_it_applies() {
+        let injection = EnvInjection::new(env_profile(
+            BTreeMap::new(),
+            vec![
+                EnvOpProfile::Set {
+                    key: "A".to_string(),
+                    value: String::new(),
+                },
+                EnvOpProfile::Append {
+                    key: "B".to_string(),
+                    value: " ".to_string(),
+                    separator: None,
+                    dedup: false,
+                    path: false,
+                },
+            ],
+        ));
+        assert_eq!(
+            injection.validate(),
+            vec![
+                "env var value for `A` must not be empty; set `allow_empty` or use `set_empty`",
+                "env var value for `B` must not be empty",
+            ]
+        );
     }
 
     #[test]
     fn prepend_path_with_dedup() {
         let mut vars = BTreeMap::new();
-        vars.insert("PATH".to_string(), "/usr/bin:/bin".to_string());
-        let injection = EnvInjection::new(EnvProfile {
-            enabled: true,
+        vars.insert("PATH".to_string(), "/usr/bin:/bin".into());
+        let injection = EnvInjection::new(env_profile(
             vars,
-            ops: vec![EnvOpProfile::Prepend {
+            vec![EnvOpProfile::Prepend {
                 key: "PATH".to_string(),
                 value: "/custom/bin:/usr/bin".to_string(),
                 separator: Some("os".to_string()),
                 dedup: true,
+                path: false,
             }],
-        });
+        ));
         let app = TestApp::new("/tmp/envlock-res", BTreeMap::new());
 
-        let exports = injection.export(&app).expect("export should pass");
+        let exports = injection
+            .export(&app, &BTreeMap::new())
+            .expect("export should pass");
         let path = exports
             .into_iter()
             .find(|(k, _)| k == "PATH")
             .map(|(_, v)| v)
             .expect("PATH should exist");
-        assert_eq!(path, "/custom/bin:/usr/bin:/bin");
+        assert_eq!(path.as_deref(), Some("/custom/bin:/usr/bin:/bin"));
     }
 
     #[test]
@@ -384,15 +792,16 @@ mod tests {
             "/tmp/envlock-res",
             BTreeMap::from([(key.to_string(), "present".to_string())]),
         );
-        let injection = EnvInjection::new(EnvProfile {
-            enabled: true,
-            vars: BTreeMap::new(),
-            ops: vec![EnvOpProfile::SetIfAbsent {
+        let injection = EnvInjection::new(env_profile(
+            BTreeMap::new(),
+            vec![EnvOpProfile::SetIfAbsent {
                 key: key.to_string(),
                 value: "fallback".to_string(),
             }],
-        });
-        let exports = injection.export(&app).expect("export should pass");
+        ));
+        let exports = injection
+            .export(&app, &BTreeMap::new())
+            .expect("export should pass");
         assert!(!exports.iter().any(|(k, _)| k == key));
     }
 
@@ -401,6 +810,7 @@ mod tests {
         let resolved = resolve_resource_refs(
             "resource://kubeconfig/xx.yaml",
             std::path::Path::new("/tmp/envlock-res"),
+            false,
         )
         .expect("resource path should resolve");
         assert_eq!(resolved, "/tmp/envlock-res/kubeconfig/xx.yaml");
@@ -411,6 +821,7 @@ mod tests {
         let resolved = resolve_resource_refs(
             "resource://kubeconfig/xx.yaml:resource://kubeconfig/yy.yaml",
             std::path::Path::new("/tmp/envlock-res"),
+            false,
         )
         .expect("multiple resource paths should resolve");
         assert_eq!(
@@ -428,8 +839,9 @@ mod tests {
         std::fs::write(&cfg, "{\"default_agent\":\"alpha\"}")
             .expect("resource content should be written");
 
-        let resolved = resolve_resource_refs("resource-content://opencode/alpha.json", temp.path())
-            .expect("resource content should resolve");
+        let resolved =
+            resolve_resource_refs("resource-content://opencode/alpha.json", temp.path(), false)
+                .expect("resource content should resolve");
         assert_eq!(resolved, "{\"default_agent\":\"alpha\"}");
     }
 
@@ -439,8 +851,9 @@ mod tests {
         std::fs::write(temp.path().join("token.txt"), "ALPHA_ONLY")
             .expect("resource content should be written");
 
-        let resolved = resolve_resource_refs("A=resource-content://token.txt;B=1", temp.path())
-            .expect("resource content with separator should resolve");
+        let resolved =
+            resolve_resource_refs("A=resource-content://token.txt;B=1", temp.path(), false)
+                .expect("resource content with separator should resolve");
         assert_eq!(resolved, "A=ALPHA_ONLY;B=1");
     }
 
@@ -449,8 +862,275 @@ mod tests {
         let err = resolve_resource_refs(
             "resource-content://missing.json",
             std::path::Path::new("/tmp/envlock-res"),
+            false,
         )
         .expect_err("missing content file should fail");
         assert!(err.to_string().contains("failed to read resource content"));
     }
+
+    #[test]
+    fn resolves_trimmed_base64_and_json_pointer_resources() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        std::fs::write(temp.path().join("token.txt"), "secret\n")
+            .expect("resource content should be written");
+        std::fs::write(
+            temp.path().join("app.json"),
+            r#"{"db":{"host":"db.internal","port":5432},"tags":["a"]}"#,
+        )
+        .expect("resource content should be written");
+
+        let resolved = resolve_resource_refs(
+            "T=resource-content-trim://token.txt;B=resource-base64://token.txt",
+            temp.path(),
+            false,
+        )
+        .expect("trim and base64 resources should resolve");
+        assert_eq!(resolved, "T=secret;B=c2VjcmV0Cg==");
+
+        let resolved = resolve_resource_refs(
+            "resource-json://app.json#/db/host resource-json://app.json#/db/port resource-json://app.json#/tags",
+            temp.path(),
+            false,
+        )
+        .expect("json pointers should resolve");
+        assert_eq!(resolved, "db.internal 5432 [\"a\"]");
+
+        let err = resolve_resource_refs("resource-json://app.json#/db/user", temp.path(), false)
+            .expect_err("missing pointer should fail");
+        let message = err.to_string();
+        assert!(
+            message.contains("resource-json://app.json#/db/user"),
+            "{message}"
+        );
+        assert!(
+            message.contains(&temp.path().join("app.json").display().to_string()),
+            "{message}"
+        );
+        let (references, error) = resource_references("resource-json://app.json#/db/host");
+        assert_eq!(references[0].rel, "app.json");
+        assert!(error.is_none());
+
+        let (references, error) = resource_references("resource://a.txt:resource://{b");
+        assert_eq!(references.len(), 1);
+        assert!(error.is_some());
+    }
+
+    #[test]
+    fn resolves_braced_and_percent_encoded_resource_paths() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let dir = temp.path().join("team a");
+        std::fs::create_dir_all(&dir).expect("resource dir should exist");
+        std::fs::write(dir.join("key (prod):1.txt"), "k\n")
+            .expect("resource content should be written");
+
+        let resolved = resolve_resource_refs(
+            "A=resource-content-trim://{team a/key (prod):1.txt};B=resource://team%20a/x",
+            temp.path(),
+            false,
+        )
+        .expect("braced and encoded resources should resolve");
+        assert_eq!(
+            resolved,
+            format!("A=k;B={}", temp.path().join("team a/x").display())
+        );
+        assert!(resolve_resource_refs("resource://{open", temp.path(), false).is_err());
+    }
+
+    #[test]
+    fn rejects_resource_paths_escaping_resource_home() {
+        let home = std::path::Path::new("/tmp/envlock-res");
+        for value in [
+            "resource://../secrets",
+            "resource://a/../../secrets",
+            "resource://%2E%2E/secrets",
+            "resource://{/etc/passwd}",
+        ] {
+            let err = resolve_resource_refs(value, home, false)
+                .expect_err("escaping resource path should fail");
+            assert!(
+                err.to_string().contains("escapes the resource home"),
+                "{value}: {err}"
+            );
+        }
+        assert_eq!(
+            resolve_resource_refs("resource://a/../b", home, false).expect("inner `..` is fine"),
+            "/tmp/envlock-res/a/../b"
+        );
+        assert_eq!(
+            resolve_resource_refs("resource://../shared", home, true)
+                .expect("escape should be allowed"),
+            "/tmp/envlock-res/../shared"
+        );
+    }
+
+    #[test]
+    fn placeholders_and_path_ops_resolve_against_profile_dir() {
+        let injection = EnvInjection::new(EnvProfile {
+            origin: Some(PathBuf::from("/work/team/profile.json")),
+            ..env_profile(
+                BTreeMap::from([(
+                    "TOOLS".to_string(),
+                    "${PROFILE_DIR}/tools:${ENVLOCK_HOME}".into(),
+                )]),
+                vec![EnvOpProfile::Prepend {
+                    key: "PATH".to_string(),
+                    value: "./bin:/opt/bin".to_string(),
+                    separator: None,
+                    dedup: false,
+                    path: true,
+                }],
+            )
+        });
+        let app = TestApp::new(
+            "/tmp/envlock-res",
+            BTreeMap::from([("PATH".to_string(), "/usr/bin".to_string())]),
+        );
+
+        let exports: BTreeMap<String, Option<String>> = injection
+            .export(&app, &BTreeMap::new())
+            .expect("export should pass")
+            .into_iter()
+            .collect();
+        assert_eq!(
+            exports["TOOLS"].as_deref(),
+            Some("/work/team/tools:/tmp/envlock-home")
+        );
+        assert_eq!(
+            exports["PATH"].as_deref(),
+            Some("/work/team/bin:/opt/bin:/usr/bin")
+        );
+    }
+
+    #[test]
+    fn interpolation_reads_block_writes_then_exports_then_process_env() {
+        let injection = EnvInjection::new(EnvProfile {
+            interpolate: true,
+            ..env_profile(
+                BTreeMap::from([("USER_HOME".to_string(), "/home/${USER}".into())]),
+                vec![
+                    EnvOpProfile::Set {
+                        key: "DB_URL".to_string(),
+                        value: "postgres://${DB_HOST}:${DB_PORT:-5432}/$$db".to_string(),
+                    },
+                    EnvOpProfile::Set {
+                        key: "LABEL".to_string(),
+                        value: "${DB_URL} as ${USER}".to_string(),
+                    },
+                ],
+            )
+        });
+        let mut app = TestApp::new(
+            "/tmp/envlock-res",
+            BTreeMap::from([
+                ("USER".to_string(), "dev".to_string()),
+                ("DB_HOST".to_string(), "process".to_string()),
+            ]),
+        );
+        let inherited = BTreeMap::from([("DB_HOST".to_string(), Some("db".to_string()))]);
+
+        let exports: BTreeMap<String, Option<String>> = injection
+            .export(&app, &inherited)
+            .expect("export should pass")
+            .into_iter()
+            .collect();
+        assert_eq!(exports["USER_HOME"].as_deref(), Some("/home/dev"));
+        assert_eq!(
+            exports["LABEL"].as_deref(),
+            Some("postgres://db:5432/$db as dev")
+        );
+
+        app.cfg.strict = true;
+        let unset = BTreeMap::from([("DB_HOST".to_string(), None)]);
+        let err = injection
+            .export(&app, &unset)
+            .expect_err("strict mode should reject unresolved variables")
+            .to_string();
+        assert!(err.contains("`DB_URL`"), "{err}");
+    }
+
+    #[test]
+    fn interpolated_values_are_not_resolved_as_resources() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        std::fs::write(temp.path().join("token"), "secret").expect("resource should be written");
+        let injection = EnvInjection::new(EnvProfile {
+            interpolate: true,
+            ..env_profile(
+                BTreeMap::from([
+                    ("FROM_ENV".to_string(), "${UPSTREAM}".into()),
+                    ("LITERAL".to_string(), "resource-content://token".into()),
+                    ("ESCAPED".to_string(), "$${PROFILE_DIR}".into()),
+                ]),
+                Vec::new(),
+            )
+        });
+        let app = TestApp::new(
+            &temp.path().display().to_string(),
+            BTreeMap::from([(
+                "UPSTREAM".to_string(),
+                "resource-content://token".to_string(),
+            )]),
+        );
+
+        let exports: BTreeMap<String, Option<String>> = injection
+            .export(&app, &BTreeMap::new())
+            .expect("export should pass")
+            .into_iter()
+            .collect();
+        assert_eq!(
+            exports["FROM_ENV"].as_deref(),
+            Some("resource-content://token")
+        );
+        assert_eq!(exports["LITERAL"].as_deref(), Some("secret"));
+        assert_eq!(exports["ESCAPED"].as_deref(), Some("${PROFILE_DIR}"));
+    }
+
+    #[test]
+    fn remove_and_normalize_clean_inherited_path() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let root = temp.path().canonicalize().expect("temp dir should resolve");
+        let bin = root.join("bin");
+        std::fs::create_dir_all(&bin).expect("bin dir should exist");
+        let inherited_path = [
+            bin.display().to_string(),
+            "/usr/local/bin".to_string(),
+            "/opt/conda/envs/base/bin".to_string(),
+            root.join("missing").display().to_string(),
+            format!("{}/../bin", bin.display()),
+        ]
+        .join(":");
+        let injection = EnvInjection::new(env_profile(
+            BTreeMap::new(),
+            vec![
+                EnvOpProfile::Remove {
+                    key: "PATH".to_string(),
+                    value: "/usr/local/bin:/opt/conda/*".to_string(),
+                    separator: Some(":".to_string()),
+                },
+                EnvOpProfile::Normalize {
+                    key: "PATH".to_string(),
+                    separator: Some(":".to_string()),
+                },
+                EnvOpProfile::Remove {
+                    key: "ABSENT".to_string(),
+                    value: "x".to_string(),
+                    separator: None,
+                },
+            ],
+        ));
+        let app = TestApp::new(
+            "/tmp/envlock-res",
+            BTreeMap::from([("PATH".to_string(), inherited_path)]),
+        );
+
+        let exports: BTreeMap<String, Option<String>> = injection
+            .export(&app, &BTreeMap::new())
+            .expect("export should pass")
+            .into_iter()
+            .collect();
+        assert_eq!(
+            exports["PATH"].as_deref(),
+            Some(bin.display().to_string().as_str())
+        );
+        assert!(!exports.contains_key("ABSENT"));
+    }
 }
@@ -0,0 +1,234 @@
+use anyhow::{Result, bail};
+
+use super::placeholders::Placeholders;
+use crate::core::app::EnvReader;
+
+/// A run of expanded text.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Piece {
+    /// Text as written in the input, with `$$` escapes undone.
+    Literal(String),
+    /// The value of a placeholder or variable reference.
+    Substituted(String),
+}
+
+/// Expands `$` references in one pass, so a `$$` escape also covers
+/// placeholders.
+///
+/// With `env`, expands `${VAR}`, `${VAR:-default}`, `${VAR:?message}` and
+/// `$VAR`, and `$$` yields a literal `$`. A reference with no value expands to
+/// an empty string, or fails when `strict` is set. `:-` and `:?` also treat an
+/// empty value as missing. Without `env`, only placeholders expand and other
+/// text is kept as written. Placeholders win over variables of the same name.
+pub(crate) struct Expander<'a> {
+    pub(crate) env: Option<&'a dyn EnvReader>,
+    pub(crate) placeholders: Option<&'a Placeholders>,
+    pub(crate) strict: bool,
+}
+
+impl Expander<'_> {
+    pub(crate) fn expand(&self, input: &str) -> Result<String> {
+        Ok(self
+            .pieces(input)?
+            .into_iter()
+            .map(|piece| match piece {
+                Piece::Literal(text) | Piece::Substituted(text) => text,
+            })
+            .collect())
+    }
+
+    /// Splits the expansion of `input` into literal and substituted runs;
+    /// adjacent literal text is merged.
+    pub(crate) fn pieces(&self, input: &str) -> Result<Vec<Piece>> {
+        let mut pieces = Pieces::default();
+        let mut rest = input;
+        while let Some(start) = rest.find('$') {
+            pieces.literal(&rest[..start]);
+            let after = &rest[start + 1..];
+            if let Some(braced) = after.strip_prefix('{') {
+                let end = braced.find('}');
+                let placeholder = end.and_then(|end| {
+                    self.placeholders
+                        .and_then(|placeholders| placeholders.lookup(&braced[..end]))
+                });
+                if let (Some(end), Some(value)) = (end, placeholder) {
+                    pieces.substituted(value.to_string_lossy().into_owned());
+                    rest = &braced[end + 1..];
+                    continue;
+                }
+                let Some(env) = self.env else {
+                    pieces.literal("${");
+                    rest = braced;
+                    continue;
+                };
+                let Some(end) = end else {
+                    bail!("unterminated variable reference in `{input}`");
+                };
+                pieces.substituted(expand_braced(&braced[..end], env, self.strict)?);
+                rest = &braced[end + 1..];
+                continue;
+            }
+            let Some(env) = self.env else {
+                pieces.literal("$");
+                rest = after;
+                continue;
+            };
+            if let Some(tail) = after.strip_prefix('$') {
+                pieces.literal("$");
+                rest = tail;
+                continue;
+            }
+            let end = after
+                .find(|ch: char| !is_name_char(ch))
+                .unwrap_or(after.len());
+            if end == 0 {
+                pieces.literal("$");
+            } else {
+                pieces.substituted(lookup(&after[..end], env, self.strict)?);
+            }
+            rest = &after[end..];
+        }
+        pieces.literal(rest);
+        Ok(pieces.0)
+    }
+}
+
+#[derive(Default)]
+struct Pieces(Vec<Piece>);
+
+impl Pieces {
+    fn literal(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match self.0.last_mut() {
+            Some(Piece::Literal(last)) => last.push_str(text),
+            _ => self.0.push(Piece::Literal(text.to_string())),
+        }
+    }
+
+    fn substituted(&mut self, value: String) {
+        self.0.push(Piece::Substituted(value));
+    }
+}
+
+fn expand_braced(expr: &str, env: &dyn EnvReader, strict: bool) -> Result<String> {
+    if let Some((name, default)) = expr.split_once(":-") {
+        validate_name(name)?;
+        return Ok(env
+            .var(name)
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| default.to_string()));
+    }
+    if let Some((name, message)) = expr.split_once(":?") {
+        validate_name(name)?;
+        return match env.var(name).filter(|value| !value.is_empty()) {
+            Some(value) => Ok(value),
+            None if message.is_empty() => bail!("`{name}` is not set"),
+            None => bail!("`{name}` is not set: {message}"),
+        };
+    }
+    validate_name(expr)?;
+    lookup(expr, env, strict)
+}
+
+fn lookup(name: &str, env: &dyn EnvReader, strict: bool) -> Result<String> {
+    match env.var(name) {
+        Some(value) => Ok(value),
+        None if strict => bail!("unresolved variable `{name}` in strict mode"),
+        None => Ok(String::new()),
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || !name.chars().all(is_name_char) {
+        bail!("invalid variable reference `${{{name}}}`");
+    }
+    Ok(())
+}
+
+fn is_name_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    use super::*;
+
+    struct MockEnv(BTreeMap<&'static str, &'static str>);
+
+    impl EnvReader for MockEnv {
+        fn var(&self, key: &str) -> Option<String> {
+            self.0.get(key).map(|value| value.to_string())
+        }
+    }
+
+    #[test]
+    fn expands_defaults_errors_and_escapes() {
+        let env = MockEnv(BTreeMap::from([("HOST", "db"), ("EMPTY", "")]));
+        let expand = |input: &str, strict: bool| {
+            Expander {
+                env: Some(&env),
+                placeholders: None,
+                strict,
+            }
+            .expand(input)
+        };
+
+        assert_eq!(
+            expand("$HOST:${PORT:-5432}/${EMPTY:-app}", false).unwrap(),
+            "db:5432/app"
+        );
+        assert_eq!(expand("cost $$5 ${MISSING}", false).unwrap(), "cost $5 ");
+        assert_eq!(expand("trailing $", true).unwrap(), "trailing $");
+
+        let err = expand("${MISSING}", true).unwrap_err().to_string();
+        assert!(err.contains("unresolved variable `MISSING`"), "{err}");
+        let err = expand("${TOKEN:?set TOKEN first}", false)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "`TOKEN` is not set: set TOKEN first");
+        assert!(expand("${HOST", false).is_err());
+        assert!(expand("${BAD NAME}", false).is_err());
+    }
+
+    #[test]
+    fn escapes_placeholders_and_splits_substitutions() {
+        let env = MockEnv(BTreeMap::from([("HOST", "db")]));
+        let placeholders = Placeholders::for_profile(
+            Path::new("/work/team/app.json"),
+            Path::new("/home/me/.envlock"),
+            Path::new("/home/me/.envlock/resources"),
+        );
+        let expander = Expander {
+            env: Some(&env),
+            placeholders: Some(&placeholders),
+            strict: false,
+        };
+
+        assert_eq!(
+            expander.expand("$${PROFILE_DIR} ${PROFILE_DIR}").unwrap(),
+            "${PROFILE_DIR} /work/team"
+        );
+        assert_eq!(
+            expander.pieces("a$$b:$HOST/c").unwrap(),
+            vec![
+                Piece::Literal("a$b:".to_string()),
+                Piece::Substituted("db".to_string()),
+                Piece::Literal("/c".to_string()),
+            ]
+        );
+
+        let placeholders_only = Expander {
+            env: None,
+            ..expander
+        };
+        assert_eq!(
+            placeholders_only.expand("$$ $HOST ${HOST} ${").unwrap(),
+            "$$ $HOST ${HOST} ${"
+        );
+    }
+}
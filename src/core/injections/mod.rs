@@ -1,21 +1,27 @@
 mod command;
 mod env;
+mod interpolate;
+pub(crate) mod placeholders;
 mod symlink;
 
-use anyhow::{Context, Result, anyhow};
-use std::collections::BTreeMap;
+use anyhow::{Context, Result, anyhow, bail};
+use std::collections::{BTreeMap, BTreeSet};
 use tracing::{debug, info};
 
-use crate::core::app::AppContext;
+use crate::core::app::{AppContext, EnvReader};
 use crate::core::profile::InjectionProfile;
 use command::CommandInjection;
 use env::EnvInjection;
+pub(crate) use env::{resource_references, separator_value};
 use symlink::SymlinkInjection;
 
+/// One lifecycle result: a key and its value, or `None` when the key is unset.
+pub type Export = (String, Option<String>);
+
 pub fn execute_lifecycle(
     app: &dyn AppContext,
     specs: Vec<InjectionProfile>,
-) -> Result<Vec<(String, String)>> {
+) -> Result<Vec<Export>> {
     with_registered_exports(app, specs, |exports| Ok(exports.to_vec()))
 }
 
@@ -25,24 +31,17 @@ pub fn with_registered_exports<T, F>(
     work: F,
 ) -> Result<T>
 where
-    F: FnOnce(&[(String, String)]) -> Result<T>,
+    F: FnOnce(&[Export]) -> Result<T>,
 {
-    let mut injections = build_injections(specs);
+    let config = app.config();
+    let specs = plan(specs, &config.enable_ids, &config.disable_ids)?;
+    let mut injections = build_injections(app, specs);
     info!(
         injection_count = injections.len(),
         "starting injection lifecycle"
     );
 
-    for injection in &injections {
-        debug!(
-            injection = injection.name(),
-            stage = "validate",
-            "running stage"
-        );
-        injection
-            .validate()
-            .with_context(|| format!("{} validation failed", injection.name()))?;
-    }
+    validate_injections(&injections)?;
 
     let (registered, register_result) = register_injections(&mut injections);
     if let Err(register_err) = register_result {
@@ -68,18 +67,46 @@ where
     }
 }
 
+/// Validates every injection before any of them registers, and reports all
+/// problems together.
+fn validate_injections(injections: &[RuntimeInjection]) -> Result<()> {
+    let mut problems = Vec::new();
+    for injection in injections {
+        debug!(
+            injection = %injection.label(),
+            stage = "validate",
+            "running stage"
+        );
+        let label = injection.label();
+        problems.extend(
+            injection
+                .validate()
+                .into_iter()
+                .map(|problem| format!("{label}: {problem}")),
+        );
+    }
+    if problems.is_empty() {
+        return Ok(());
+    }
+    bail!(
+        "validation failed with {} problem(s):\n  {}",
+        problems.len(),
+        problems.join("\n  ")
+    );
+}
+
 fn register_injections(injections: &mut [RuntimeInjection]) -> (usize, Result<()>) {
     let mut registered = 0usize;
     for injection in injections {
         debug!(
-            injection = injection.name(),
+            injection = %injection.label(),
             stage = "register",
             "running stage"
         );
         if let Err(err) = injection.register() {
             return (
                 registered,
-                Err(err).with_context(|| format!("{} registration failed", injection.name())),
+                Err(err).with_context(|| format!("{} registration failed", injection.label())),
             );
         }
         registered += 1;
@@ -93,29 +120,26 @@ fn run_export_and_work<T, F>(
     work: F,
 ) -> Result<T>
 where
-    F: FnOnce(&[(String, String)]) -> Result<T>,
+    F: FnOnce(&[Export]) -> Result<T>,
 {
     let exports = collect_exports(app, injections)?;
     work(&exports)
 }
 
-fn collect_exports(
-    app: &dyn AppContext,
-    injections: &[RuntimeInjection],
-) -> Result<Vec<(String, String)>> {
+fn collect_exports(app: &dyn AppContext, injections: &[RuntimeInjection]) -> Result<Vec<Export>> {
     let mut exports = Vec::new();
     let mut inherited = BTreeMap::new();
     for injection in injections {
         debug!(
-            injection = injection.name(),
+            injection = %injection.label(),
             stage = "export",
             "running stage"
         );
         let exported = injection
             .export(app, &inherited)
-            .with_context(|| format!("{} export failed", injection.name()))?;
+            .with_context(|| format!("{} export failed", injection.label()))?;
         debug!(
-            injection = injection.name(),
+            injection = %injection.label(),
             export_count = exported.len(),
             "export stage completed"
         );
@@ -128,35 +152,200 @@ fn collect_exports(
     Ok(exports)
 }
 
+/// Process environment seen through the exports of earlier injections; an
+/// unset export hides the process value.
+pub(crate) struct OverlayEnv<'a> {
+    base: &'a dyn EnvReader,
+    overlay: &'a BTreeMap<String, Option<String>>,
+}
+
+impl<'a> OverlayEnv<'a> {
+    pub(crate) fn new(
+        base: &'a dyn EnvReader,
+        inherited: &'a BTreeMap<String, Option<String>>,
+    ) -> Self {
+        Self {
+            base,
+            overlay: inherited,
+        }
+    }
+}
+
+impl EnvReader for OverlayEnv<'_> {
+    fn var(&self, key: &str) -> Option<String> {
+        match self.overlay.get(key) {
+            Some(value) => value.clone(),
+            None => self.base.var(key),
+        }
+    }
+}
+
 fn shutdown_registered(injections: &mut [RuntimeInjection], registered: usize) -> Result<()> {
     for idx in (0..registered).rev() {
         debug!(
-            injection = injections[idx].name(),
+            injection = %injections[idx].label(),
             stage = "shutdown",
             "running stage"
         );
         injections[idx]
             .shutdown()
-            .with_context(|| format!("{} shutdown failed", injections[idx].name()))?;
+            .with_context(|| format!("{} shutdown failed", injections[idx].label()))?;
     }
     info!(registered_count = registered, "shutdown completed");
     Ok(())
 }
 
-fn build_injections(specs: Vec<InjectionProfile>) -> Vec<RuntimeInjection> {
+/// Why `spec` will not run: `disabled`, or the unmet part of its `when` clause.
+pub fn skip_reason(spec: &InjectionProfile, env: &dyn EnvReader) -> Option<String> {
+    if !spec.enabled() {
+        return Some("disabled".to_string());
+    }
+    spec.when()
+        .and_then(|when| when.unmet_reason(env))
+        .map(|reason| format!("when: {reason}"))
+}
+
+/// The injections `run` executes, in order: ids are checked, `--enable` and
+/// `--disable` applied and `depends_on` resolved. `enabled` and `when` are
+/// left for the caller to evaluate.
+pub fn plan(
+    mut specs: Vec<InjectionProfile>,
+    enable: &[String],
+    disable: &[String],
+) -> Result<Vec<InjectionProfile>> {
+    check_unique_ids(&specs)?;
+    apply_toggles(&mut specs, enable, disable)?;
+    let order = run_order(&specs)?;
+    let mut slots: Vec<Option<InjectionProfile>> = specs.into_iter().map(Some).collect();
+    Ok(order
+        .into_iter()
+        .filter_map(|idx| slots[idx].take())
+        .collect())
+}
+
+/// Ids must be unique so toggles and `depends_on` name exactly one injection.
+fn check_unique_ids(specs: &[InjectionProfile]) -> Result<()> {
+    let mut seen = BTreeSet::new();
+    for id in specs.iter().filter_map(InjectionProfile::id) {
+        if !seen.insert(id) {
+            bail!("duplicate injection id `{id}`");
+        }
+    }
+    Ok(())
+}
+
+/// Forces injections on or off by id, as requested by `--enable`/`--disable`.
+pub fn apply_toggles(
+    specs: &mut [InjectionProfile],
+    enable: &[String],
+    disable: &[String],
+) -> Result<()> {
+    if let Some(id) = enable.iter().find(|id| disable.contains(id)) {
+        bail!("injection `{id}` is both enabled and disabled");
+    }
+    for (ids, enabled) in [(enable, true), (disable, false)] {
+        for id in ids {
+            let Some(idx) = specs.iter().position(|spec| spec.id() == Some(id.as_str())) else {
+                bail!("unknown injection id `{id}` (known: {})", known_ids(specs));
+            };
+            specs[idx].set_enabled(enabled);
+        }
+    }
+    Ok(())
+}
+
+fn known_ids(specs: &[InjectionProfile]) -> String {
+    let ids: Vec<&str> = specs.iter().filter_map(InjectionProfile::id).collect();
+    if ids.is_empty() {
+        "none".to_string()
+    } else {
+        ids.join(", ")
+    }
+}
+
+/// Indices of `specs` in run order: each injection runs after everything in
+/// its `depends_on`, otherwise profile order is kept. Duplicate, unknown and
+/// cyclic ids fail.
+pub fn run_order(specs: &[InjectionProfile]) -> Result<Vec<usize>> {
+    check_unique_ids(specs)?;
+    if specs.iter().all(|spec| spec.depends_on().is_empty()) {
+        return Ok((0..specs.len()).collect());
+    }
+
+    let index_by_id: BTreeMap<&str, usize> = specs
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, spec)| spec.id().map(|id| (id, idx)))
+        .collect();
+
+    let mut deps = Vec::with_capacity(specs.len());
+    for spec in specs {
+        let mut resolved = BTreeSet::new();
+        for dep in spec.depends_on() {
+            let Some(&idx) = index_by_id.get(dep.as_str()) else {
+                bail!(
+                    "injection `{}` depends on unknown id `{dep}`",
+                    spec.id().unwrap_or("<anonymous>")
+                );
+            };
+            resolved.insert(idx);
+        }
+        deps.push(resolved);
+    }
+
+    let mut done = vec![false; specs.len()];
+    let mut order = Vec::with_capacity(specs.len());
+    while order.len() < specs.len() {
+        let next =
+            (0..specs.len()).find(|&idx| !done[idx] && deps[idx].iter().all(|&dep| done[dep]));
+        let Some(idx) = next else {
+            bail!(
+                "injection dependency cycle detected: {}",
+                describe_cycle(specs, &deps, &done)
+            );
+        };
+        done[idx] = true;
+        order.push(idx);
+    }
+    Ok(order)
+}
+
+fn describe_cycle(specs: &[InjectionProfile], deps: &[BTreeSet<usize>], done: &[bool]) -> String {
+    let mut path = Vec::new();
+    let mut current = (0..specs.len()).find(|&idx| !done[idx]);
+    while let Some(idx) = current {
+        if let Some(start) = path.iter().position(|&seen| seen == idx) {
+            path.drain(..start);
+            path.push(idx);
+            break;
+        }
+        path.push(idx);
+        current = deps[idx].iter().copied().find(|&dep| !done[dep]);
+    }
+    path.iter()
+        .map(|&idx| specs[idx].id().unwrap_or("<anonymous>"))
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+fn build_injections(app: &dyn AppContext, specs: Vec<InjectionProfile>) -> Vec<RuntimeInjection> {
     let mut injections = Vec::new();
     for spec in specs {
+        if let Some(reason) = skip_reason(&spec, app.env()) {
+            debug!(reason = %reason, "skipping injection");
+            continue;
+        }
         match spec {
-            InjectionProfile::Env(cfg) if cfg.enabled => {
+            InjectionProfile::Env(cfg) => {
                 injections.push(RuntimeInjection::Env(EnvInjection::new(cfg)));
             }
-            InjectionProfile::Command(cfg) if cfg.enabled => {
+            InjectionProfile::Command(cfg) => {
                 injections.push(RuntimeInjection::Command(CommandInjection::new(cfg)));
             }
-            InjectionProfile::Symlink(cfg) if cfg.enabled => {
+            InjectionProfile::Symlink(cfg) => {
                 injections.push(RuntimeInjection::Symlink(SymlinkInjection::new(cfg)));
             }
-            _ => {}
+            InjectionProfile::Include(_) => {}
         }
     }
     debug!(
@@ -181,7 +370,20 @@ impl RuntimeInjection {
         }
     }
 
-    fn validate(&self) -> Result<()> {
+    /// `<type> \`<id>\`` when the injection has an id, otherwise just the type.
+    fn label(&self) -> String {
+        let id = match self {
+            Self::Env(inner) => inner.id(),
+            Self::Command(inner) => inner.id(),
+            Self::Symlink(inner) => inner.id(),
+        };
+        match id {
+            Some(id) => format!("{} `{}`", self.name(), id),
+            None => self.name().to_string(),
+        }
+    }
+
+    fn validate(&self) -> Vec<String> {
         match self {
             Self::Env(inner) => inner.validate(),
             Self::Command(inner) => inner.validate(),
@@ -200,10 +402,10 @@ impl RuntimeInjection {
     fn export(
         &self,
         app: &dyn AppContext,
-        inherited: &BTreeMap<String, String>,
-    ) -> Result<Vec<(String, String)>> {
+        inherited: &BTreeMap<String, Option<String>>,
+    ) -> Result<Vec<Export>> {
         match self {
-            Self::Env(inner) => inner.export(app),
+            Self::Env(inner) => inner.export(app, inherited),
             Self::Command(inner) => inner.export(app, inherited),
             Self::Symlink(inner) => inner.export(),
         }
@@ -222,7 +424,8 @@ impl RuntimeInjection {
 mod tests {
     use super::*;
     use crate::core::app::{AppContext, CommandRunner, EnvReader};
-    use crate::core::config::{LogFormat, OutputMode, RuntimeConfig};
+    use crate::core::config::{LogFormat, OutputMode, ProfileSource, RuntimeConfig};
+    use crate::core::profile::{EnvOpProfile, EnvProfile, EnvValue};
     use std::collections::BTreeMap;
     use std::path::PathBuf;
     use tempfile::TempDir;
@@ -257,12 +460,17 @@ mod tests {
         fn new() -> Self {
             Self {
                 cfg: RuntimeConfig {
-                    profile_path: PathBuf::from("/tmp/unused.json"),
+                    profile_paths: vec![PathBuf::from("/tmp/unused.json")],
+                    profile_source: ProfileSource::Explicit,
                     output_mode: OutputMode::Shell,
                     strict: false,
                     log_level: LevelFilter::WARN,
                     log_format: LogFormat::Text,
                     command: None,
+                    params: BTreeMap::new(),
+                    enable_ids: Vec::new(),
+                    disable_ids: Vec::new(),
+                    stage: None,
                     envlock_home: PathBuf::from("/tmp/envlock-home"),
                     resource_home: PathBuf::from("/tmp/envlock-res"),
                 },
@@ -286,34 +494,84 @@ mod tests {
         }
     }
 
+    /// An enabled env block with every field but `vars` and `ops` at its
+    /// default.
+    pub(super) fn env_profile(
+        vars: BTreeMap<String, EnvValue>,
+        ops: Vec<EnvOpProfile>,
+    ) -> EnvProfile {
+        EnvProfile {
+            enabled: true,
+            id: None,
+            depends_on: Vec::new(),
+            vars,
+            separator: None,
+            ops,
+            allow_empty: false,
+            allow_resource_escape: false,
+            interpolate: false,
+            when: None,
+            origin: None,
+        }
+    }
+
     #[test]
     fn skip_disabled_env_injection() {
         let specs = vec![
-            InjectionProfile::Env(crate::core::profile::EnvProfile {
+            InjectionProfile::Env(EnvProfile {
                 enabled: false,
-                vars: BTreeMap::from([("A".to_string(), "1".to_string())]),
-                ops: Vec::new(),
-            }),
-            InjectionProfile::Env(crate::core::profile::EnvProfile {
-                enabled: true,
-                vars: BTreeMap::from([("B".to_string(), "2".to_string())]),
-                ops: Vec::new(),
+                ..env_profile(BTreeMap::from([("A".to_string(), "1".into())]), Vec::new())
             }),
+            InjectionProfile::Env(env_profile(
+                BTreeMap::from([("B".to_string(), "2".into())]),
+                Vec::new(),
+            )),
         ];
 
         let app = TestApp::new();
         let exports = execute_lifecycle(&app, specs).expect("lifecycle should pass");
         assert_eq!(exports.len(), 1);
-        assert!(exports.contains(&("B".to_string(), "2".to_string())));
+        assert!(exports.contains(&("B".to_string(), Some("2".to_string()))));
+    }
+
+    #[test]
+    fn skip_injection_when_condition_is_unmet() {
+        let specs = vec![
+            InjectionProfile::Env(EnvProfile {
+                when: Some(crate::core::profile::Condition::Env(
+                    crate::core::profile::EnvCondition {
+                        key: "CI".to_string(),
+                        equals: None,
+                    },
+                )),
+                ..env_profile(BTreeMap::from([("A".to_string(), "1".into())]), Vec::new())
+            }),
+            InjectionProfile::Env(EnvProfile {
+                when: Some(crate::core::profile::Condition::Not(Box::new(
+                    crate::core::profile::Condition::Env(crate::core::profile::EnvCondition {
+                        key: "CI".to_string(),
+                        equals: None,
+                    }),
+                ))),
+                ..env_profile(BTreeMap::from([("B".to_string(), "2".into())]), Vec::new())
+            }),
+        ];
+
+        let app = TestApp::new();
+        assert_eq!(
+            skip_reason(&specs[0], app.env()),
+            Some("when: env CI is set (unset)".to_string())
+        );
+        let exports = execute_lifecycle(&app, specs).expect("lifecycle should pass");
+        assert_eq!(exports, vec![("B".to_string(), Some("2".to_string()))]);
     }
 
     #[test]
     fn fail_validation_when_env_key_is_empty() {
-        let specs = vec![InjectionProfile::Env(crate::core::profile::EnvProfile {
-            enabled: true,
-            vars: BTreeMap::from([("   ".to_string(), "1".to_string())]),
-            ops: Vec::new(),
-        })];
+        let specs = vec![InjectionProfile::Env(env_profile(
+            BTreeMap::from([("   ".to_string(), "1".into())]),
+            Vec::new(),
+        ))];
 
         let app = TestApp::new();
         let err = execute_lifecycle(&app, specs).expect_err("empty env key should fail");
@@ -330,23 +588,26 @@ mod tests {
                     "-lc".to_string(),
                     "printf \"export CMD_A='1'\\nCMD_B=2\\n\"".to_string(),
                 ],
+                when: None,
+                id: None,
+                depends_on: Vec::new(),
+                origin: None,
             },
         )];
 
         let app = TestApp::new();
         let exports = execute_lifecycle(&app, specs).expect("command lifecycle should pass");
-        assert!(exports.contains(&("CMD_A".to_string(), "1".to_string())));
-        assert!(exports.contains(&("CMD_B".to_string(), "2".to_string())));
+        assert!(exports.contains(&("CMD_A".to_string(), Some("1".to_string()))));
+        assert!(exports.contains(&("CMD_B".to_string(), Some("2".to_string()))));
     }
 
     #[test]
     fn command_injection_observes_prior_exports() {
         let specs = vec![
-            InjectionProfile::Env(crate::core::profile::EnvProfile {
-                enabled: true,
-                vars: BTreeMap::from([("BASE".to_string(), "seed".to_string())]),
-                ops: Vec::new(),
-            }),
+            InjectionProfile::Env(env_profile(
+                BTreeMap::from([("BASE".to_string(), "seed".into())]),
+                Vec::new(),
+            )),
             InjectionProfile::Command(crate::core::profile::CommandProfile {
                 enabled: true,
                 program: "bash".to_string(),
@@ -354,13 +615,64 @@ mod tests {
                     "-lc".to_string(),
                     "printf 'export DERIVED=${BASE}-ok\\n'".to_string(),
                 ],
+                when: None,
+                id: None,
+                depends_on: Vec::new(),
+                origin: None,
             }),
         ];
 
         let app = TestApp::new();
         let exports = execute_lifecycle(&app, specs).expect("command should see prior exports");
-        assert!(exports.contains(&("BASE".to_string(), "seed".to_string())));
-        assert!(exports.contains(&("DERIVED".to_string(), "seed-ok".to_string())));
+        assert!(exports.contains(&("BASE".to_string(), Some("seed".to_string()))));
+        assert!(exports.contains(&("DERIVED".to_string(), Some("seed-ok".to_string()))));
+    }
+
+    #[test]
+    fn env_ops_chain_across_blocks_and_command_outputs() {
+        let env_ops =
+            |ops: Vec<EnvOpProfile>| InjectionProfile::Env(env_profile(BTreeMap::new(), ops));
+        let prepend = |value: &str| EnvOpProfile::Prepend {
+            key: "PATH".to_string(),
+            value: value.to_string(),
+            separator: Some(":".to_string()),
+            dedup: false,
+            path: false,
+        };
+        let specs = vec![
+            env_ops(vec![prepend("/first")]),
+            InjectionProfile::Command(crate::core::profile::CommandProfile {
+                enabled: true,
+                program: "sh".to_string(),
+                args: vec![
+                    "-c".to_string(),
+                    "printf 'export TOOL_HOME=/opt/tool\\n'".to_string(),
+                ],
+                when: None,
+                id: None,
+                depends_on: Vec::new(),
+                origin: None,
+            }),
+            env_ops(vec![
+                prepend("/second"),
+                EnvOpProfile::SetIfAbsent {
+                    key: "TOOL_HOME".to_string(),
+                    value: "/fallback".to_string(),
+                },
+            ]),
+        ];
+
+        let app = TestApp::new();
+        let exports = execute_lifecycle(&app, specs).expect("lifecycle should pass");
+        let last = |key: &str| {
+            exports
+                .iter()
+                .rev()
+                .find(|(k, _)| k == key)
+                .and_then(|(_, v)| v.as_deref())
+        };
+        assert_eq!(last("PATH"), Some("/second:/first"));
+        assert_eq!(last("TOOL_HOME"), Some("/opt/tool"));
     }
 
     #[test]
@@ -382,6 +694,10 @@ mod tests {
                 target: target_a.clone(),
                 on_exist: crate::core::profile::SymlinkOnExist::Error,
                 cleanup: true,
+                when: None,
+                id: None,
+                depends_on: Vec::new(),
+                origin: None,
             }),
             InjectionProfile::Symlink(crate::core::profile::SymlinkProfile {
                 enabled: true,
@@ -389,6 +705,10 @@ mod tests {
                 target: target_b,
                 on_exist: crate::core::profile::SymlinkOnExist::Error,
                 cleanup: true,
+                when: None,
+                id: None,
+                depends_on: Vec::new(),
+                origin: None,
             }),
         ];
 
@@ -400,4 +720,81 @@ mod tests {
             "first symlink should be rolled back on later register failure"
         );
     }
+
+    fn env_with_id(id: &str, depends_on: &[&str], key: &str) -> InjectionProfile {
+        InjectionProfile::Env(EnvProfile {
+            id: Some(id.to_string()),
+            depends_on: depends_on.iter().map(ToString::to_string).collect(),
+            ..env_profile(BTreeMap::from([(key.to_string(), id.into())]), Vec::new())
+        })
+    }
+
+    #[test]
+    fn depends_on_reorders_injections() {
+        let specs = vec![
+            env_with_id("app", &["base"], "APP"),
+            env_with_id("extra", &[], "EXTRA"),
+            env_with_id("base", &[], "BASE"),
+        ];
+
+        let app = TestApp::new();
+        let exports = execute_lifecycle(&app, specs).expect("lifecycle should pass");
+        let keys: Vec<&str> = exports.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["EXTRA", "BASE", "APP"]);
+    }
+
+    #[test]
+    fn depends_on_rejects_cycles_and_unknown_ids() {
+        let app = TestApp::new();
+        let specs = vec![env_with_id("a", &["b"], "A"), env_with_id("b", &["a"], "B")];
+        let err = execute_lifecycle(&app, specs).expect_err("cycle should fail");
+        assert_eq!(
+            err.to_string(),
+            "injection dependency cycle detected: a -> b -> a"
+        );
+
+        let specs = vec![env_with_id("a", &["missing"], "A")];
+        let err = execute_lifecycle(&app, specs).expect_err("unknown dependency should fail");
+        assert!(err.to_string().contains("depends on unknown id `missing`"));
+    }
+
+    #[test]
+    fn duplicate_ids_fail_without_depends_on() {
+        let app = TestApp::new();
+        let specs = vec![env_with_id("dup", &[], "A"), env_with_id("dup", &[], "B")];
+        let err = execute_lifecycle(&app, specs).expect_err("duplicate id should fail");
+        assert_eq!(err.to_string(), "duplicate injection id `dup`");
+    }
+
+    #[test]
+    fn toggles_flip_injections_by_id() {
+        let mut specs = vec![env_with_id("a", &[], "A"), env_with_id("b", &[], "B")];
+        specs[1].set_enabled(false);
+
+        apply_toggles(&mut specs, &["b".to_string()], &["a".to_string()])
+            .expect("toggles should apply");
+        assert!(!specs[0].enabled());
+        assert!(specs[1].enabled());
+
+        let err = apply_toggles(&mut specs, &["c".to_string()], &[]).expect_err("unknown id");
+        assert_eq!(err.to_string(), "unknown injection id `c` (known: a, b)");
+    }
+
+    #[test]
+    fn validation_errors_name_the_injection_id() {
+        let specs = vec![InjectionProfile::Env(EnvProfile {
+            id: Some("proxy".to_string()),
+            ..env_profile(
+                BTreeMap::from([("   ".to_string(), "1".into())]),
+                Vec::new(),
+            )
+        })];
+
+        let app = TestApp::new();
+        let err = execute_lifecycle(&app, specs).expect_err("empty env key should fail");
+        assert_eq!(
+            err.to_string(),
+            "validation failed with 1 problem(s):\n  env `proxy`: env var key must not be empty"
+        );
+    }
 }
@@ -0,0 +1,138 @@
+use std::path::{Path, PathBuf};
+
+use path_absolutize::Absolutize;
+
+use super::interpolate::{Expander, Piece};
+use crate::core::app::AppContext;
+
+/// Built-in `${NAME}` placeholders expanded in env values and command args.
+/// Unknown names are left untouched.
+pub(crate) struct Placeholders {
+    profile_dir: PathBuf,
+    envlock_home: PathBuf,
+    resource_home: PathBuf,
+    cwd: Option<PathBuf>,
+}
+
+impl Placeholders {
+    /// `origin` is the profile file that declared the injection; it falls back
+    /// to the last stacked profile for injections built outside the loader.
+    pub(crate) fn new(app: &dyn AppContext, origin: Option<&Path>) -> Self {
+        let config = app.config();
+        let profile_file = origin
+            .or_else(|| config.profile_paths.last().map(PathBuf::as_path))
+            .unwrap_or(Path::new("."));
+        Self::for_profile(profile_file, &config.envlock_home, &config.resource_home)
+    }
+
+    /// Builds placeholders for `profile_file` without a runtime config.
+    pub(crate) fn for_profile(
+        profile_file: &Path,
+        envlock_home: &Path,
+        resource_home: &Path,
+    ) -> Self {
+        let profile_dir = profile_file
+            .parent()
+            .unwrap_or(Path::new("."))
+            .absolutize()
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_else(|_| profile_file.to_path_buf());
+        Self {
+            profile_dir,
+            envlock_home: envlock_home.to_path_buf(),
+            resource_home: resource_home.to_path_buf(),
+            cwd: std::env::current_dir().ok(),
+        }
+    }
+
+    pub(crate) fn profile_dir(&self) -> &Path {
+        &self.profile_dir
+    }
+
+    pub(crate) fn expand(&self, input: &str) -> String {
+        Expander {
+            env: None,
+            placeholders: Some(self),
+            strict: false,
+        }
+        .expand(input)
+        .expect("expanding placeholders alone cannot fail")
+    }
+
+    /// The runs of `input` written literally, between placeholders.
+    pub(crate) fn literals(&self, input: &str) -> Vec<String> {
+        Expander {
+            env: None,
+            placeholders: Some(self),
+            strict: false,
+        }
+        .pieces(input)
+        .expect("expanding placeholders alone cannot fail")
+        .into_iter()
+        .filter_map(|piece| match piece {
+            Piece::Literal(text) => Some(text),
+            Piece::Substituted(_) => None,
+        })
+        .collect()
+    }
+
+    pub(crate) fn lookup(&self, name: &str) -> Option<&Path> {
+        match name {
+            "PROFILE_DIR" => Some(&self.profile_dir),
+            "ENVLOCK_HOME" => Some(&self.envlock_home),
+            "RESOURCE_HOME" => Some(&self.resource_home),
+            "CWD" => self.cwd.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Makes every relative segment of a separator-joined path list absolute
+/// against `base_dir`.
+pub(crate) fn absolutize_segments(value: &str, separator: &str, base_dir: &Path) -> String {
+    value
+        .split(separator)
+        .map(|segment| {
+            let path = Path::new(segment);
+            if segment.is_empty() || path.is_absolute() {
+                return segment.to_string();
+            }
+            path.absolutize_from(base_dir)
+                .map(|abs| abs.to_string_lossy().into_owned())
+                .unwrap_or_else(|_| segment.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders() -> Placeholders {
+        Placeholders {
+            profile_dir: PathBuf::from("/work/team"),
+            envlock_home: PathBuf::from("/home/me/.envlock"),
+            resource_home: PathBuf::from("/home/me/.envlock/resources"),
+            cwd: Some(PathBuf::from("/work/repo")),
+        }
+    }
+
+    #[test]
+    fn expand_replaces_known_placeholders_only() {
+        let expanded = placeholders()
+            .expand("${PROFILE_DIR}/bin:${CWD}:${ENVLOCK_HOME}/x:${RESOURCE_HOME}:${HOME}:${");
+        assert_eq!(
+            expanded,
+            "/work/team/bin:/work/repo:/home/me/.envlock/x:/home/me/.envlock/resources:${HOME}:${"
+        );
+    }
+
+    #[test]
+    fn absolutize_segments_resolves_relative_entries() {
+        assert_eq!(
+            absolutize_segments("./bin:/usr/bin:../tools", ":", Path::new("/work/team")),
+            "/work/team/bin:/usr/bin:/work/tools"
+        );
+    }
+}
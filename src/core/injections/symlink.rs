@@ -2,6 +2,7 @@ use std::path::Path;
 
 use anyhow::{Result, bail};
 
+use super::Export;
 use crate::core::profile::{SymlinkOnExist, SymlinkProfile};
 
 pub(crate) struct SymlinkInjection {
@@ -17,21 +18,28 @@ impl SymlinkInjection {
         }
     }
 
+    pub(crate) fn id(&self) -> Option<&str> {
+        self.cfg.id.as_deref()
+    }
+
     pub(crate) fn name(&self) -> &'static str {
         "symlink"
     }
 
-    pub(crate) fn validate(&self) -> Result<()> {
+    pub(crate) fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
         if self.cfg.source.to_string_lossy().trim().is_empty() {
-            bail!("source must not be empty");
+            problems.push("source must not be empty".to_string());
+        } else if !self.cfg.source.exists() {
+            problems.push(format!(
+                "source does not exist: {}",
+                self.cfg.source.display()
+            ));
         }
         if self.cfg.target.to_string_lossy().trim().is_empty() {
-            bail!("target must not be empty");
+            problems.push("target must not be empty".to_string());
         }
-        if !self.cfg.source.exists() {
-            bail!("source does not exist: {}", self.cfg.source.display());
-        }
-        Ok(())
+        problems
     }
 
     pub(crate) fn register(&mut self) -> Result<()> {
@@ -40,7 +48,7 @@ impl SymlinkInjection {
         Ok(())
     }
 
-    pub(crate) fn export(&self) -> Result<Vec<(String, String)>> {
+    pub(crate) fn export(&self) -> Result<Vec<Export>> {
         Ok(Vec::new())
     }
 
@@ -112,6 +120,10 @@ mod tests {
             target: target.clone(),
             on_exist: SymlinkOnExist::Error,
             cleanup: true,
+            when: None,
+            id: None,
+            depends_on: Vec::new(),
+            origin: None,
         });
 
         let err = injection
@@ -136,6 +148,10 @@ mod tests {
             target: target.clone(),
             on_exist: SymlinkOnExist::Error,
             cleanup: true,
+            when: None,
+            id: None,
+            depends_on: Vec::new(),
+            origin: None,
         });
 
         injection
@@ -168,6 +184,10 @@ mod tests {
             target: target.clone(),
             on_exist: SymlinkOnExist::Replace,
             cleanup: true,
+            when: None,
+            id: None,
+            depends_on: Vec::new(),
+            origin: None,
         });
 
         injection.register().expect("replace mode should succeed");
@@ -0,0 +1,523 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::app::ProcessEnv;
+use super::injections::placeholders::{Placeholders, absolutize_segments};
+use super::injections::{resource_references, run_order, separator_value, skip_reason};
+use super::profile::{
+    CommandProfile, Condition, EnvOpProfile, EnvProfile, InjectionProfile, Profile, SymlinkOnExist,
+    SymlinkProfile,
+};
+
+/// Semantic checks run by `envlock profiles lint`. Codes are stable; new rules
+/// get new codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintRule {
+    DisabledInjection,
+    OverwrittenSet,
+    UnsetThenSetIfAbsent,
+    MissingPathSegment,
+    MissingResource,
+    SymlinkTargetExists,
+    ProgramNotFound,
+    InvalidResourceUri,
+}
+
+impl LintRule {
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::DisabledInjection => "L001",
+            Self::OverwrittenSet => "L002",
+            Self::UnsetThenSetIfAbsent => "L003",
+            Self::MissingPathSegment => "L004",
+            Self::MissingResource => "L005",
+            Self::SymlinkTargetExists => "L006",
+            Self::ProgramNotFound => "L007",
+            Self::InvalidResourceUri => "L008",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::DisabledInjection => "disabled-injection",
+            Self::OverwrittenSet => "overwritten-set",
+            Self::UnsetThenSetIfAbsent => "unset-then-set-if-absent",
+            Self::MissingPathSegment => "missing-path-segment",
+            Self::MissingResource => "missing-resource",
+            Self::SymlinkTargetExists => "symlink-target-exists",
+            Self::ProgramNotFound => "program-not-found",
+            Self::InvalidResourceUri => "invalid-resource-uri",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LintFinding {
+    pub code: &'static str,
+    pub rule: &'static str,
+    /// `#<position> <type>` plus the injection id when it has one.
+    pub injection: String,
+    pub origin: Option<PathBuf>,
+    pub message: String,
+}
+
+/// Host facts the filesystem rules check against.
+pub struct LintContext {
+    pub envlock_home: PathBuf,
+    pub resource_home: PathBuf,
+    /// `PATH` used to look up command programs.
+    pub search_path: Option<OsString>,
+}
+
+/// Runs every rule over a loaded profile, in the order `run` executes its
+/// injections. Ordering rules only follow injections whose `when` holds on
+/// this host.
+pub fn lint(profile: &Profile, ctx: &LintContext) -> Vec<LintFinding> {
+    let mut linter = Linter {
+        ctx,
+        findings: Vec::new(),
+        last_set: BTreeMap::new(),
+        last_unset: BTreeMap::new(),
+    };
+    // An unresolvable order fails `run` itself; lint the file order instead.
+    let order =
+        run_order(&profile.injections).unwrap_or_else(|_| (0..profile.injections.len()).collect());
+    for index in order {
+        let injection = &profile.injections[index];
+        let label = injection_label(index, injection);
+        let origin = injection.origin().map(Path::to_path_buf);
+        if !injection.enabled() {
+            linter.push(
+                LintRule::DisabledInjection,
+                &label,
+                &origin,
+                "injection is disabled and never runs".to_string(),
+            );
+            continue;
+        }
+        let runs_here = skip_reason(injection, &ProcessEnv).is_none();
+        match injection {
+            InjectionProfile::Env(spec) => {
+                linter.env(spec, &label, &origin);
+                if runs_here {
+                    linter.env_writes(spec, &label, &origin);
+                }
+            }
+            InjectionProfile::Command(spec) => {
+                if runs_here {
+                    // The program may read anything exported so far.
+                    linter.last_set.clear();
+                }
+                linter.command(spec, &label, &origin);
+            }
+            InjectionProfile::Symlink(spec) => linter.symlink(spec, &label, &origin),
+            InjectionProfile::Include(_) => {}
+        }
+    }
+    linter.findings
+}
+
+struct Linter<'a> {
+    ctx: &'a LintContext,
+    findings: Vec<LintFinding>,
+    /// Keys whose last write was a plain `set`, with the writing injection.
+    last_set: BTreeMap<String, Writer>,
+    /// Keys whose last write was an `unset`, with the writing injection.
+    last_unset: BTreeMap<String, Writer>,
+}
+
+/// An injection that wrote a key, and the `when` guard it runs under.
+struct Writer {
+    label: String,
+    when: Option<Condition>,
+}
+
+impl Writer {
+    fn new(label: &str, when: &Option<Condition>) -> Self {
+        Self {
+            label: label.to_string(),
+            when: when.clone(),
+        }
+    }
+
+    /// Writers with different guards may not run together, so ordering
+    /// findings between them would be guesses.
+    fn runs_with(&self, when: &Option<Condition>) -> bool {
+        self.when == *when
+    }
+}
+
+impl Linter<'_> {
+    fn push(&mut self, rule: LintRule, label: &str, origin: &Option<PathBuf>, message: String) {
+        self.findings.push(LintFinding {
+            code: rule.code(),
+            rule: rule.name(),
+            injection: label.to_string(),
+            origin: origin.clone(),
+            message,
+        });
+    }
+
+    fn env(&mut self, spec: &EnvProfile, label: &str, origin: &Option<PathBuf>) {
+        let placeholders = Placeholders::for_profile(
+            origin.as_deref().unwrap_or(Path::new(".")),
+            &self.ctx.envlock_home,
+            &self.ctx.resource_home,
+        );
+        let separator = separator_value(&spec.separator);
+        for value in spec.vars.values() {
+            let value = value.to_env_string(separator);
+            self.check_resources(&placeholders, &value, label, origin);
+        }
+        for op in &spec.ops {
+            match op {
+                EnvOpProfile::Set { value, .. } | EnvOpProfile::SetIfAbsent { value, .. } => {
+                    self.check_resources(&placeholders, value, label, origin);
+                }
+                EnvOpProfile::Prepend {
+                    key,
+                    value,
+                    separator,
+                    path,
+                    ..
+                }
+                | EnvOpProfile::Append {
+                    key,
+                    value,
+                    separator,
+                    path,
+                    ..
+                } => {
+                    self.check_resources(&placeholders, value, label, origin);
+                    let expanded = placeholders.expand(value);
+                    if key == "PATH" || *path {
+                        self.check_path_segments(
+                            &expanded,
+                            separator_value(separator),
+                            path.then(|| placeholders.profile_dir()),
+                            label,
+                            origin,
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Tracks the block's writes for the ordering rules.
+    fn env_writes(&mut self, spec: &EnvProfile, label: &str, origin: &Option<PathBuf>) {
+        for key in spec.vars.keys() {
+            self.record_set(key, label, &spec.when, origin);
+        }
+        for op in &spec.ops {
+            match op {
+                EnvOpProfile::Set { key, .. } | EnvOpProfile::SetEmpty { key } => {
+                    self.record_set(key, label, &spec.when, origin);
+                }
+                EnvOpProfile::SetIfAbsent { key, .. } => {
+                    if let Some(unset_by) = self.last_unset.remove(key)
+                        && unset_by.runs_with(&spec.when)
+                    {
+                        self.push(
+                            LintRule::UnsetThenSetIfAbsent,
+                            label,
+                            origin,
+                            format!(
+                                "set_if_absent of `{key}` follows an unset in {}, so it always applies; use set",
+                                unset_by.label
+                            ),
+                        );
+                    }
+                    self.last_set.remove(key);
+                }
+                EnvOpProfile::Prepend { key, .. } | EnvOpProfile::Append { key, .. } => {
+                    self.last_set.remove(key);
+                    self.last_unset.remove(key);
+                }
+                // These read the current value and leave an unset key unset.
+                EnvOpProfile::Remove { key, .. }
+                | EnvOpProfile::Normalize { key, .. }
+                | EnvOpProfile::Replace { key, .. } => {
+                    self.last_set.remove(key);
+                }
+                EnvOpProfile::Copy { from, to } | EnvOpProfile::Move { from, to } => {
+                    self.last_set.remove(from);
+                    self.last_set.remove(to);
+                    self.last_unset.remove(to);
+                }
+                EnvOpProfile::Unset { key } => {
+                    self.last_set.remove(key);
+                    self.last_unset
+                        .insert(key.clone(), Writer::new(label, &spec.when));
+                }
+            }
+        }
+    }
+
+    fn record_set(
+        &mut self,
+        key: &str,
+        label: &str,
+        when: &Option<Condition>,
+        origin: &Option<PathBuf>,
+    ) {
+        if let Some(previous) = self
+            .last_set
+            .insert(key.to_string(), Writer::new(label, when))
+            && previous.runs_with(when)
+        {
+            self.push(
+                LintRule::OverwrittenSet,
+                label,
+                origin,
+                format!(
+                    "`{key}` set in {} is overwritten before it is used",
+                    previous.label
+                ),
+            );
+        }
+        self.last_unset.remove(key);
+    }
+
+    /// Checks the references written in `value`; placeholders never expand
+    /// into one.
+    fn check_resources(
+        &mut self,
+        placeholders: &Placeholders,
+        value: &str,
+        label: &str,
+        origin: &Option<PathBuf>,
+    ) {
+        for text in placeholders.literals(value) {
+            let (references, error) = resource_references(&text);
+            for reference in references {
+                let path = self.ctx.resource_home.join(&reference.rel);
+                if !path.exists() {
+                    self.push(
+                        LintRule::MissingResource,
+                        label,
+                        origin,
+                        format!("resource `{}` not found: {}", reference.rel, path.display()),
+                    );
+                }
+            }
+            if let Some(error) = error {
+                self.push(
+                    LintRule::InvalidResourceUri,
+                    label,
+                    origin,
+                    format!("{error:#}"),
+                );
+            }
+        }
+    }
+
+    /// Checks absolute segments, and relative ones when `path: true` anchors
+    /// them to the profile directory. Segments still holding `$` are skipped.
+    fn check_path_segments(
+        &mut self,
+        value: &str,
+        separator: &str,
+        profile_dir: Option<&Path>,
+        label: &str,
+        origin: &Option<PathBuf>,
+    ) {
+        // Resolve `resource://` first: its prefix contains the `:` separator.
+        let mut resolved_value = value.to_string();
+        for reference in resource_references(value).0 {
+            let resolved = self.ctx.resource_home.join(&reference.rel);
+            resolved_value =
+                resolved_value.replacen(reference.uri(value), &resolved.to_string_lossy(), 1);
+        }
+        let value = match profile_dir {
+            Some(dir) => absolutize_segments(&resolved_value, separator, dir),
+            None => resolved_value,
+        };
+        for segment in value.split(separator) {
+            let resolved = Path::new(segment);
+            if segment.is_empty()
+                || segment.contains('$')
+                || !resolved.is_absolute()
+                || resolved.exists()
+            {
+                continue;
+            }
+            self.push(
+                LintRule::MissingPathSegment,
+                label,
+                origin,
+                format!("path segment does not exist: {}", resolved.display()),
+            );
+        }
+    }
+
+    fn command(&mut self, spec: &CommandProfile, label: &str, origin: &Option<PathBuf>) {
+        if spec.program.contains('$') || self.program_exists(&spec.program) {
+            return;
+        }
+        self.push(
+            LintRule::ProgramNotFound,
+            label,
+            origin,
+            format!("program `{}` not found on PATH", spec.program),
+        );
+    }
+
+    fn program_exists(&self, program: &str) -> bool {
+        if program.contains(['/', '\\']) {
+            return Path::new(program).is_file();
+        }
+        self.ctx.search_path.as_ref().is_some_and(|search_path| {
+            std::env::split_paths(search_path).any(|dir| {
+                let candidate = dir.join(program);
+                candidate.is_file() || (cfg!(windows) && candidate.with_extension("exe").is_file())
+            })
+        })
+    }
+
+    fn symlink(&mut self, spec: &SymlinkProfile, label: &str, origin: &Option<PathBuf>) {
+        if spec.on_exist == SymlinkOnExist::Error && std::fs::symlink_metadata(&spec.target).is_ok()
+        {
+            self.push(
+                LintRule::SymlinkTargetExists,
+                label,
+                origin,
+                format!(
+                    "target already exists and on_exist is `error`: {}",
+                    spec.target.display()
+                ),
+            );
+        }
+    }
+}
+
+fn injection_label(index: usize, injection: &InjectionProfile) -> String {
+    let kind = match injection {
+        InjectionProfile::Env(_) => "env",
+        InjectionProfile::Command(_) => "command",
+        InjectionProfile::Symlink(_) => "symlink",
+        InjectionProfile::Include(_) => "include",
+    };
+    match injection.id() {
+        Some(id) => format!("#{} {kind} `{id}`", index + 1),
+        None => format!("#{} {kind}", index + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(raw: &str, ctx: &LintContext) -> Vec<&'static str> {
+        let profile: Profile = serde_json::from_str(raw).expect("profile should parse");
+        lint(&profile, ctx).into_iter().map(|f| f.code).collect()
+    }
+
+    fn context(root: &Path) -> LintContext {
+        LintContext {
+            envlock_home: root.to_path_buf(),
+            resource_home: root.join("resources"),
+            search_path: Some(root.join("bin").into_os_string()),
+        }
+    }
+
+    #[test]
+    fn env_rules_track_writes_across_injections() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let raw = r#"{"injections":[
+            {"type":"env","vars":{"A":"1","B":"1"},"ops":[{"op":"unset","key":"C"}]},
+            {"type":"env","enabled":false,"vars":{"A":"x"}},
+            {"type":"env","vars":{"A":"2"},"ops":[
+                {"op":"append","key":"B","value":"2"},
+                {"op":"set","key":"B","value":"3"},
+                {"op":"set_if_absent","key":"C","value":"4"}
+            ]}
+        ]}"#;
+        assert_eq!(
+            codes(raw, &context(temp.path())),
+            vec!["L001", "L002", "L003"]
+        );
+    }
+
+    #[test]
+    fn env_rules_skip_writers_with_different_when_guards() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let raw = r#"{"injections":[
+            {"type":"env","vars":{"A":"1"},"ops":[{"op":"unset","key":"C"}]},
+            {"type":"env","when":{"not":{"env":{"key":"ENVLOCK_LINT_TEST_UNSET"}}},"vars":{"A":"2"}},
+            {"type":"env","when":{"any":[{"os":"linux"},{"not":{"os":"linux"}}]},"vars":{"A":"3"},"ops":[
+                {"op":"set_if_absent","key":"C","value":"4"}
+            ]},
+            {"type":"env","when":{"any":[{"os":"linux"},{"not":{"os":"linux"}}]},"vars":{"A":"4"}}
+        ]}"#;
+        assert_eq!(codes(raw, &context(temp.path())), vec!["L002"]);
+    }
+
+    #[test]
+    fn ordering_rules_ignore_injections_that_do_not_run_here() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let raw = r#"{"injections":[
+            {"type":"env","vars":{"A":"1"}},
+            {"type":"command","when":{"env":{"key":"ENVLOCK_LINT_TEST_UNSET"}},"program":"true"},
+            {"type":"env","when":{"env":{"key":"ENVLOCK_LINT_TEST_UNSET"}},"ops":[
+                {"op":"unset","key":"C"}
+            ]},
+            {"type":"env","vars":{"A":"2"},"ops":[{"op":"set_if_absent","key":"C","value":"3"}]}
+        ]}"#;
+        assert_eq!(codes(raw, &context(temp.path())), vec!["L007", "L002"]);
+    }
+
+    #[test]
+    fn ordering_rules_follow_depends_on() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let raw = r#"{"injections":[
+            {"type":"env","id":"late","depends_on":["early"],"vars":{"A":"2"}},
+            {"type":"env","id":"early","vars":{"A":"1"}}
+        ]}"#;
+        let profile: Profile = serde_json::from_str(raw).expect("profile should parse");
+        let findings = lint(&profile, &context(temp.path()));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].code, "L002");
+        assert_eq!(findings[0].injection, "#1 env `late`");
+        assert!(findings[0].message.contains("#2 env `early`"));
+    }
+
+    #[test]
+    fn filesystem_rules_check_paths_resources_links_and_programs() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("bin")).expect("bin dir should exist");
+        std::fs::create_dir_all(root.join("resources")).expect("resource dir should exist");
+        std::fs::write(root.join("bin/tool"), "").expect("program should be written");
+        std::fs::write(root.join("resources/kube.yaml"), "").expect("resource should be written");
+        std::fs::write(root.join("taken"), "").expect("target should be written");
+        let raw = format!(
+            r#"{{"injections":[
+                {{"type":"env","vars":{{"KUBECONFIG":"resource://kube.yaml:resource://gone.yaml"}},
+                  "ops":[{{"op":"prepend","key":"PATH","value":"{root}/bin:resource://kube.yaml:{root}/missing:$HOME/bin"}}]}},
+                {{"type":"command","program":"tool"}},
+                {{"type":"command","program":"no-such-tool"}},
+                {{"type":"symlink","source":"{root}/bin/tool","target":"{root}/taken"}},
+                {{"type":"symlink","source":"{root}/bin/tool","target":"{root}/taken","on_exist":"replace"}}
+            ]}}"#,
+            root = root.display()
+        );
+        assert_eq!(
+            codes(&raw, &context(root)),
+            vec!["L005", "L004", "L007", "L006"]
+        );
+    }
+
+    #[test]
+    fn malformed_resource_uri_keeps_earlier_references() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let raw = r#"{"injections":[
+            {"type":"env","vars":{"A":"resource://gone.yaml:resource://{broken"}}
+        ]}"#;
+        assert_eq!(codes(raw, &context(temp.path())), vec!["L005", "L008"]);
+    }
+}
@@ -2,6 +2,8 @@ pub mod alias_store;
 pub mod app;
 pub mod config;
 pub(crate) mod env_key;
+pub(crate) mod glob;
 pub mod injections;
+pub mod lint;
 pub mod profile;
 pub mod runtime;
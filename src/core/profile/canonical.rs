@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::format::ProfileFormat;
+use super::migrate;
+
+/// A profile file next to its canonical rendering.
+pub struct Canonical {
+    pub format: ProfileFormat,
+    pub original: String,
+    pub formatted: String,
+    /// The original has YAML or TOML comments that `formatted` would drop.
+    pub has_comments: bool,
+}
+
+impl Canonical {
+    pub fn is_formatted(&self) -> bool {
+        self.original == self.formatted
+    }
+
+    /// Whether writing `formatted` back would lose content.
+    pub fn is_lossy(&self) -> bool {
+        self.has_comments && !self.is_formatted()
+    }
+}
+
+/// Re-emits the profile at `path` in its own format with the field order of
+/// the profile types and defaults omitted. The file is not touched.
+///
+/// Only the file itself is parsed: `extends`, `include` and params are kept
+/// as written. Rendering drops comments, so callers must not write back a
+/// lossy result.
+pub fn canonicalize(path: &Path) -> Result<Canonical> {
+    let original = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read profile file: {}", path.display()))?;
+    let format = ProfileFormat::from_path(path)?;
+    let profile = migrate::parse(path, &original, format)?;
+    let formatted = format
+        .render(&profile)
+        .with_context(|| format!("failed to render profile: {}", path.display()))?;
+    Ok(Canonical {
+        format,
+        has_comments: format.has_comments(&original),
+        original,
+        formatted,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_output_orders_fields_and_drops_defaults() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let path = temp.path().join("team.json");
+        std::fs::write(
+            &path,
+            r#"{"injections":[{"target":"b","source":"a","type":"symlink","cleanup":true,"on_exist":"error"},{"vars":{"B":"2","A":"1"},"type":"env","enabled":true,"ops":[{"value":"./bin","op":"prepend","key":"PATH","dedup":false}]}],"schema_version":1}"#,
+        )
+        .expect("profile should be written");
+
+        let canonical = canonicalize(&path).expect("profile should format");
+        assert!(!canonical.is_formatted());
+        assert_eq!(
+            canonical.formatted,
+            r#"{
+  "schema_version": 1,
+  "injections": [
+    {
+      "type": "symlink",
+      "source": "a",
+      "target": "b"
+    },
+    {
+      "type": "env",
+      "vars": {
+        "A": "1",
+        "B": "2"
+      },
+      "ops": [
+        {
+          "op": "prepend",
+          "key": "PATH",
+          "value": "./bin"
+        }
+      ]
+    }
+  ]
+}
+"#
+        );
+
+        std::fs::write(&path, &canonical.formatted).expect("profile should be written");
+        assert!(
+            canonicalize(&path)
+                .expect("profile should format")
+                .is_formatted()
+        );
+    }
+
+    #[test]
+    fn commented_yaml_is_lossy() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let path = temp.path().join("team.yaml");
+        std::fs::write(
+            &path,
+            "# team profile\ninjections:\n  - type: env\n    vars: { A: '1' }\n",
+        )
+        .expect("profile should be written");
+
+        let canonical = canonicalize(&path).expect("profile should format");
+        assert!(canonical.is_lossy());
+    }
+}
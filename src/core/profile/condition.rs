@@ -0,0 +1,217 @@
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::core::app::EnvReader;
+use crate::core::glob::glob_match;
+
+/// Guard evaluated before an injection is built; unmet conditions skip it.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Condition {
+    /// Matches `std::env::consts::OS` (`linux`, `macos`, `windows`, ...).
+    Os(String),
+    /// Matches `std::env::consts::ARCH` (`x86_64`, `aarch64`, ...).
+    Arch(String),
+    /// Glob (`*`, `?`) matched case-insensitively against the host name.
+    Hostname(String),
+    /// Presence, or exact value, of a variable in the current environment.
+    Env(EnvCondition),
+    /// Path relative to the declaring profile; `~` is expanded.
+    FileExists(PathBuf),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EnvCondition {
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub equals: Option<String>,
+}
+
+impl Condition {
+    pub fn holds(&self, env: &dyn EnvReader) -> bool {
+        self.unmet_reason(env).is_none()
+    }
+
+    /// Returns `None` when the condition holds, otherwise why it does not.
+    pub fn unmet_reason(&self, env: &dyn EnvReader) -> Option<String> {
+        match self {
+            Self::Os(expected) => mismatch(
+                self,
+                std::env::consts::OS,
+                expected.eq_ignore_ascii_case(std::env::consts::OS),
+            ),
+            Self::Arch(expected) => mismatch(
+                self,
+                std::env::consts::ARCH,
+                expected.eq_ignore_ascii_case(std::env::consts::ARCH),
+            ),
+            Self::Hostname(pattern) => {
+                let host = current_hostname();
+                let matched = glob_match(&pattern.to_lowercase(), &host.to_lowercase());
+                mismatch(self, &host, matched)
+            }
+            Self::Env(cond) => match (env.var(&cond.key), &cond.equals) {
+                (None, _) => Some(format!("{} (unset)", self.describe())),
+                (Some(actual), Some(expected)) if &actual != expected => {
+                    Some(format!("{} (actual: {})", self.describe(), actual))
+                }
+                _ => None,
+            },
+            Self::FileExists(path) => {
+                (!path.exists()).then(|| format!("{} (missing)", self.describe()))
+            }
+            Self::All(conditions) => conditions.iter().find_map(|cond| cond.unmet_reason(env)),
+            Self::Any(conditions) => {
+                let reasons: Vec<String> = conditions
+                    .iter()
+                    .map(|cond| cond.unmet_reason(env))
+                    .collect::<Option<_>>()?;
+                Some(format!("any({})", reasons.join("; ")))
+            }
+            Self::Not(inner) => inner
+                .holds(env)
+                .then(|| format!("{} (inner condition holds)", self.describe())),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Os(value) => format!("os == {value}"),
+            Self::Arch(value) => format!("arch == {value}"),
+            Self::Hostname(pattern) => format!("hostname ~ {pattern}"),
+            Self::Env(EnvCondition { key, equals: None }) => format!("env {key} is set"),
+            Self::Env(EnvCondition {
+                key,
+                equals: Some(value),
+            }) => format!("env {key} == {value}"),
+            Self::FileExists(path) => format!("file_exists {}", path.display()),
+            Self::All(conditions) => format!("all({})", describe_list(conditions)),
+            Self::Any(conditions) => format!("any({})", describe_list(conditions)),
+            Self::Not(inner) => format!("not({})", inner.describe()),
+        }
+    }
+
+    /// Visits every `file_exists` path so the loader can resolve it.
+    pub(super) fn paths_mut(&mut self, visit: &mut dyn FnMut(&mut PathBuf)) {
+        match self {
+            Self::FileExists(path) => visit(path),
+            Self::All(conditions) | Self::Any(conditions) => {
+                for cond in conditions {
+                    cond.paths_mut(visit);
+                }
+            }
+            Self::Not(inner) => inner.paths_mut(visit),
+            Self::Os(_) | Self::Arch(_) | Self::Hostname(_) | Self::Env(_) => {}
+        }
+    }
+}
+
+fn mismatch(cond: &Condition, actual: &str, matched: bool) -> Option<String> {
+    (!matched).then(|| format!("{} (actual: {})", cond.describe(), actual))
+}
+
+fn describe_list(conditions: &[Condition]) -> String {
+    conditions
+        .iter()
+        .map(Condition::describe)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn current_hostname() -> String {
+    gethostname::gethostname().to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    struct MockEnv {
+        vars: BTreeMap<String, String>,
+    }
+
+    impl EnvReader for MockEnv {
+        fn var(&self, key: &str) -> Option<String> {
+            self.vars.get(key).cloned()
+        }
+    }
+
+    fn env(pairs: &[(&str, &str)]) -> MockEnv {
+        MockEnv {
+            vars: pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    fn parse(raw: &str) -> Condition {
+        serde_json::from_str(raw).expect("condition should parse")
+    }
+
+    #[test]
+    fn os_and_arch_match_current_host() {
+        let env = env(&[]);
+        let current = format!(
+            r#"{{"all":[{{"os":"{}"}},{{"arch":"{}"}}]}}"#,
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        );
+        assert!(parse(&current).holds(&env));
+        let reason = parse(r#"{"os":"plan9"}"#)
+            .unmet_reason(&env)
+            .expect("foreign os should not hold");
+        assert_eq!(
+            reason,
+            format!("os == plan9 (actual: {})", std::env::consts::OS)
+        );
+    }
+
+    #[test]
+    fn env_presence_and_value() {
+        let env = env(&[("CI", "true")]);
+        assert!(parse(r#"{"env":{"key":"CI"}}"#).holds(&env));
+        assert!(parse(r#"{"env":{"key":"CI","equals":"true"}}"#).holds(&env));
+        assert_eq!(
+            parse(r#"{"env":{"key":"CI","equals":"false"}}"#).unmet_reason(&env),
+            Some("env CI == false (actual: true)".to_string())
+        );
+        assert_eq!(
+            parse(r#"{"env":{"key":"BUILD_BOX"}}"#).unmet_reason(&env),
+            Some("env BUILD_BOX is set (unset)".to_string())
+        );
+    }
+
+    #[test]
+    fn combinators_report_reasons() {
+        let env = env(&[("CI", "1")]);
+        assert!(parse(r#"{"any":[{"env":{"key":"NOPE"}},{"env":{"key":"CI"}}]}"#).holds(&env));
+        assert_eq!(
+            parse(r#"{"not":{"env":{"key":"CI"}}}"#).unmet_reason(&env),
+            Some("not(env CI is set) (inner condition holds)".to_string())
+        );
+        assert_eq!(
+            parse(r#"{"any":[{"env":{"key":"A"}},{"file_exists":"/definitely/missing"}]}"#)
+                .unmet_reason(&env),
+            Some(
+                "any(env A is set (unset); file_exists /definitely/missing (missing))".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn hostname_glob_matches_current_host() {
+        let env = env(&[]);
+        assert!(parse(r#"{"hostname":"*"}"#).holds(&env));
+        assert!(!parse(r#"{"hostname":"no-such-host-?-*-envlock"}"#).holds(&env));
+    }
+}
@@ -0,0 +1,260 @@
+use std::path::Path;
+
+use super::format::ProfileFormat;
+
+/// A deserializer error reduced to its message and (1-based) line/column.
+#[derive(Debug)]
+pub struct ParseFailure {
+    pub message: String,
+    pub location: Option<(usize, usize)>,
+}
+
+impl ParseFailure {
+    pub(super) fn from_json(err: serde_json::Error) -> Self {
+        let location = (err.line() > 0).then(|| (err.line(), err.column()));
+        Self {
+            message: strip_location_suffix(&err.to_string()),
+            location,
+        }
+    }
+
+    pub(super) fn from_yaml(err: serde_yaml::Error) -> Self {
+        let location = err.location().map(|loc| (loc.line(), loc.column()));
+        Self {
+            message: strip_location_suffix(&err.to_string()),
+            location,
+        }
+    }
+
+    pub(super) fn from_toml(err: toml::de::Error, raw: &str) -> Self {
+        let location = err.span().map(|span| offset_to_line_col(raw, span.start));
+        Self {
+            message: err.message().trim().to_string(),
+            location,
+        }
+    }
+}
+
+/// Renders `<path>:<line>:<column>: <message>`, pointing unknown fields and
+/// variants at their position in the source and suggesting the closest
+/// accepted name.
+pub(super) fn describe(
+    path: &Path,
+    raw: &str,
+    format: ProfileFormat,
+    failure: &ParseFailure,
+) -> String {
+    let mut message = failure.message.clone();
+    let mut location = failure.location;
+
+    let unknown = backticked_after(&message, "unknown field `")
+        .map(|name| (name, true))
+        .or_else(|| backticked_after(&message, "unknown variant `").map(|name| (name, false)));
+    if let Some((name, is_field)) = unknown {
+        if let Some(found) = locate_token(raw, &name, is_field, format, failure.location) {
+            location = Some(found);
+        }
+        let expected = expected_names(&message);
+        if let Some(suggestion) = closest_name(&name, &expected) {
+            if let Some(idx) = message.find(", expected") {
+                message.truncate(idx);
+            }
+            message.push_str(&format!(" (did you mean `{suggestion}`?)"));
+        }
+    }
+
+    match location {
+        Some((line, column)) => format!("{}:{}:{}: {}", path.display(), line, column, message),
+        None => format!("{}: {}", path.display(), message),
+    }
+}
+
+fn strip_location_suffix(message: &str) -> String {
+    let Some(idx) = message.rfind(" at line ") else {
+        return message.to_string();
+    };
+    let tail = &message[idx + " at line ".len()..];
+    let is_location = tail.split_once(" column ").is_some_and(|(line, column)| {
+        !line.is_empty()
+            && line.chars().all(|c| c.is_ascii_digit())
+            && !column.is_empty()
+            && column.chars().all(|c| c.is_ascii_digit())
+    });
+    if is_location {
+        message[..idx].to_string()
+    } else {
+        message.to_string()
+    }
+}
+
+fn backticked_after(message: &str, marker: &str) -> Option<String> {
+    let start = message.find(marker)? + marker.len();
+    let end = message[start..].find('`')?;
+    Some(message[start..start + end].to_string())
+}
+
+fn expected_names(message: &str) -> Vec<String> {
+    let Some(idx) = message.find("expected ") else {
+        return Vec::new();
+    };
+    message[idx..]
+        .split('`')
+        .skip(1)
+        .step_by(2)
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn closest_name(name: &str, candidates: &[String]) -> Option<String> {
+    let threshold = (name.chars().count() / 3).max(2);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.clone())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b_chars.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b_chars.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            current.push(substitution.min(prev[j + 1] + 1).min(current[j] + 1));
+        }
+        prev = current;
+    }
+    prev[b_chars.len()]
+}
+
+fn locate_token(
+    raw: &str,
+    name: &str,
+    is_key: bool,
+    format: ProfileFormat,
+    native: Option<(usize, usize)>,
+) -> Option<(usize, usize)> {
+    let candidates: Vec<usize> = raw
+        .match_indices(name)
+        .map(|(idx, _)| idx)
+        .filter(|idx| is_token_at(raw, *idx, name.len(), is_key))
+        .collect();
+    let native_offset = native.map(|(line, column)| line_col_to_offset(raw, line, column));
+    // JSON reports the end of the enclosing object, YAML/TOML its start.
+    let picked = match (native_offset, format) {
+        (Some(limit), ProfileFormat::Json) => candidates.iter().rev().find(|idx| **idx <= limit),
+        (Some(limit), _) => candidates.iter().find(|idx| **idx >= limit),
+        (None, _) => None,
+    }
+    .or(candidates.first())?;
+
+    let start = match raw[..*picked].chars().next_back() {
+        Some(quote @ ('"' | '\'')) => *picked - quote.len_utf8(),
+        _ => *picked,
+    };
+    Some(offset_to_line_col(raw, start))
+}
+
+fn is_token_at(raw: &str, idx: usize, len: usize, is_key: bool) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '$');
+    if raw[..idx].chars().next_back().is_some_and(is_ident) {
+        return false;
+    }
+    let mut rest = raw[idx + len..].chars().peekable();
+    if rest.peek().copied().is_some_and(is_ident) {
+        return false;
+    }
+    if !is_key {
+        return true;
+    }
+    if matches!(rest.peek(), Some('"' | '\'')) {
+        rest.next();
+    }
+    rest.find(|c| !matches!(c, ' ' | '\t'))
+        .is_some_and(|c| c == ':' || c == '=')
+}
+
+fn offset_to_line_col(raw: &str, offset: usize) -> (usize, usize) {
+    let before = &raw[..offset.min(raw.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map_or(0, |tail| tail.chars().count())
+        + 1;
+    (line, column)
+}
+
+fn line_col_to_offset(raw: &str, line: usize, column: usize) -> usize {
+    let mut offset = 0usize;
+    for (idx, text) in raw.split_inclusive('\n').enumerate() {
+        if idx + 1 == line {
+            return offset
+                + text
+                    .char_indices()
+                    .nth(column.saturating_sub(1))
+                    .map_or(text.len(), |(i, _)| i);
+        }
+        offset += text.len();
+    }
+    raw.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::profile::Profile;
+
+    fn diagnose(raw: &str, format: ProfileFormat) -> String {
+        let err = format
+            .parse::<Profile>(raw)
+            .expect_err("profile should be rejected");
+        describe(Path::new("team.profile"), raw, format, &err)
+    }
+
+    #[test]
+    fn json_unknown_field_points_at_key_with_suggestion() {
+        let raw = r#"{
+  "injections": [
+    {
+      "type": "env",
+      "ops": [
+        { "op": "prepend", "key": "PATH", "value": "/x", "separater": ":" }
+      ]
+    }
+  ]
+}"#;
+        assert_eq!(
+            diagnose(raw, ProfileFormat::Json),
+            "team.profile:6:58: unknown field `separater` (did you mean `separator`?)"
+        );
+    }
+
+    #[test]
+    fn yaml_unknown_field_points_at_key_with_suggestion() {
+        let raw = "injections:\n  - type: symlink\n    source: a\n    target: b\n    on_exists: replace\n";
+        assert_eq!(
+            diagnose(raw, ProfileFormat::Yaml),
+            "team.profile:5:5: injections: unknown field `on_exists` (did you mean `on_exist`?)"
+        );
+    }
+
+    #[test]
+    fn toml_unknown_top_level_field() {
+        let raw = "# team profile\ninjectons = []\n";
+        assert_eq!(
+            diagnose(raw, ProfileFormat::Toml),
+            "team.profile:2:1: unknown field `injectons` (did you mean `injections`?)"
+        );
+    }
+
+    #[test]
+    fn unrelated_name_keeps_expected_list() {
+        let raw = r#"{"injections":[{"type":"env","colour":"red"}]}"#;
+        let message = diagnose(raw, ProfileFormat::Json);
+        assert!(message.starts_with("team.profile:1:30: unknown field `colour`, expected"));
+        assert!(!message.contains("did you mean"));
+    }
+}
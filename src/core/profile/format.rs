@@ -0,0 +1,152 @@
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use super::diagnostics::ParseFailure;
+
+/// On-disk encodings accepted for profile files, selected by file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+/// Extensions probed, in order, when a profile is referenced by name.
+pub const PROFILE_EXTENSIONS: &[&str] = &["json", "yaml", "yml", "toml"];
+
+impl ProfileFormat {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => match Self::from_extension(ext) {
+                Some(format) => Ok(format),
+                None => bail!(
+                    "unsupported profile extension `.{}`: {} (expected .json, .yaml, .yml or .toml)",
+                    ext,
+                    path.display()
+                ),
+            },
+            None => bail!(
+                "profile file has no extension: {} (expected .json, .yaml, .yml or .toml)",
+                path.display()
+            ),
+        }
+    }
+
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Yaml => "YAML",
+            Self::Toml => "TOML",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(self, raw: &str) -> Result<T, ParseFailure> {
+        match self {
+            Self::Json => serde_json::from_str(raw).map_err(ParseFailure::from_json),
+            Self::Yaml => serde_yaml::from_str(raw).map_err(ParseFailure::from_yaml),
+            Self::Toml => toml::from_str(raw).map_err(|err| ParseFailure::from_toml(err, raw)),
+        }
+    }
+
+    /// Whether `raw` holds comments that re-serializing would lose. Errs on
+    /// the side of `true`: any `#` outside a one-line quoted string counts.
+    pub fn has_comments(self, raw: &str) -> bool {
+        if self == Self::Json {
+            return false;
+        }
+        raw.lines().any(|line| {
+            let mut quote: Option<char> = None;
+            let mut escaped = false;
+            for ch in line.chars() {
+                match quote {
+                    Some('"') if escaped => escaped = false,
+                    Some('"') if ch == '\\' => escaped = true,
+                    Some(open) if ch == open => quote = None,
+                    Some(_) => {}
+                    None if ch == '"' || ch == '\'' => quote = Some(ch),
+                    None if ch == '#' => return true,
+                    None => {}
+                }
+            }
+            false
+        })
+    }
+
+    /// Renders `value` in this format; JSON uses two-space indentation and a
+    /// trailing newline.
+    pub fn render<T: Serialize>(self, value: &T) -> Result<String> {
+        Ok(match self {
+            Self::Json => format!("{}\n", serde_json::to_string_pretty(value)?),
+            Self::Yaml => serde_yaml::to_string(value)?,
+            Self::Toml => toml::to_string(value)?,
+        })
+    }
+}
+
+pub fn is_profile_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(ProfileFormat::from_extension)
+        .is_some()
+}
+
+/// Returns the first existing `<dir>/<stem>.<ext>` over [`PROFILE_EXTENSIONS`].
+pub fn find_profile(dir: &Path, stem: &str) -> Option<std::path::PathBuf> {
+    PROFILE_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{stem}.{ext}")))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_follows_extension() {
+        assert_eq!(
+            ProfileFormat::from_path(Path::new("a.json")).expect("json should be supported"),
+            ProfileFormat::Json
+        );
+        assert_eq!(
+            ProfileFormat::from_path(Path::new("a.YML")).expect("yml should be supported"),
+            ProfileFormat::Yaml
+        );
+        assert_eq!(
+            ProfileFormat::from_path(Path::new("a.toml")).expect("toml should be supported"),
+            ProfileFormat::Toml
+        );
+        let err = ProfileFormat::from_path(Path::new("a.ini")).expect_err("ini should fail");
+        assert!(err.to_string().contains("unsupported profile extension"));
+    }
+
+    #[test]
+    fn comments_are_detected_outside_quotes() {
+        let yaml = ProfileFormat::Yaml;
+        assert!(yaml.has_comments("# team profile\ninjections: []\n"));
+        assert!(yaml.has_comments("injections: [] # none yet\n"));
+        assert!(!yaml.has_comments("vars:\n  A: \"a#b\"\n  B: 'c # d'\n"));
+        assert!(ProfileFormat::Toml.has_comments("[meta] # info\n"));
+        assert!(!ProfileFormat::Json.has_comments("{\"a\": \"#\"}"));
+    }
+}
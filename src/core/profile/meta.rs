@@ -0,0 +1,63 @@
+use anyhow::{Context, Result, bail};
+use schemars::JsonSchema;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+/// Descriptive profile metadata plus an optional envlock version requirement.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(!skip_serializing_if)]
+    pub tags: Vec<String>,
+    /// Semver range the running envlock must satisfy, e.g. `">=0.4, <1"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub requires_envlock: Option<String>,
+}
+
+impl ProfileMeta {
+    /// Fails when the running envlock does not satisfy `requires_envlock`.
+    pub fn check_version(&self) -> Result<()> {
+        check_requirement(self.requires_envlock.as_deref(), env!("CARGO_PKG_VERSION"))
+    }
+}
+
+fn check_requirement(requirement: Option<&str>, current: &str) -> Result<()> {
+    let Some(raw) = requirement else {
+        return Ok(());
+    };
+    let req = VersionReq::parse(raw)
+        .with_context(|| format!("invalid requires_envlock range `{raw}`"))?;
+    let version =
+        Version::parse(current).with_context(|| format!("invalid envlock version `{current}`"))?;
+    if !req.matches(&version) {
+        bail!(
+            "profile requires envlock {raw}, but this is envlock {current}; run `envlock self-update` to upgrade"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requirement_matches_current_version() {
+        check_requirement(None, "0.3.1").expect("no requirement should pass");
+        check_requirement(Some(">=0.3, <1"), "0.3.1").expect("range should match");
+
+        let err = check_requirement(Some(">=9.0"), "0.3.1").expect_err("too old");
+        assert!(err.to_string().contains("envlock self-update"));
+
+        let err = check_requirement(Some("not a range"), "0.3.1").expect_err("bad range");
+        assert!(err.to_string().contains("invalid requires_envlock range"));
+    }
+}
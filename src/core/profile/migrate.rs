@@ -0,0 +1,188 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+
+use super::Profile;
+use super::diagnostics;
+use super::format::ProfileFormat;
+
+/// Shape version written by `envlock profiles migrate` and understood by this loader.
+///
+/// - `0` (no `schema_version`): profiles written before versioning.
+/// - `1`: the current shape.
+///
+/// Both are parsed strictly; unknown keys are always rejected. Unversioned
+/// profiles load with a deprecation warning.
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// Result of rewriting one profile file into the current shape.
+pub struct Migration {
+    pub format: ProfileFormat,
+    pub original: String,
+    pub migrated: String,
+}
+
+impl Migration {
+    pub fn is_noop(&self) -> bool {
+        self.original == self.migrated
+    }
+}
+
+/// Parses `raw` strictly and rejects versions newer than this loader.
+///
+/// No shape has changed since version 0, so unversioned profiles parse as the
+/// current shape. When one does, upgrade the raw value here before parsing.
+pub(super) fn parse(path: &Path, raw: &str, format: ProfileFormat) -> Result<Profile> {
+    match format.parse::<Profile>(raw) {
+        Ok(profile) => {
+            check_version(profile.schema_version)?;
+            Ok(profile)
+        }
+        Err(failure) => {
+            // A newer shape may use keys this loader does not know; report
+            // the version instead of the first unknown key.
+            if let Ok(value) = format.parse::<Value>(raw) {
+                check_version(declared_version(&value))?;
+            }
+            bail!(
+                "failed to parse {}: {}",
+                format.name(),
+                diagnostics::describe(path, raw, format, &failure)
+            )
+        }
+    }
+}
+
+/// Rewrites the profile at `path` into the current shape without touching the file.
+///
+/// Stamping `schema_version` is a text edit, so key order, formatting and
+/// comments are kept.
+pub fn migrate(path: &Path) -> Result<Migration> {
+    let original = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read profile file: {}", path.display()))?;
+    let format = ProfileFormat::from_path(path)?;
+    let profile = parse(path, &original, format)?;
+    let migrated = match profile.schema_version {
+        Some(_) => original.clone(),
+        None => stamp_version(&original, format),
+    };
+    Ok(Migration {
+        format,
+        original,
+        migrated,
+    })
+}
+
+/// Adds `schema_version` as the first key without re-serializing the file.
+fn stamp_version(raw: &str, format: ProfileFormat) -> String {
+    match format {
+        ProfileFormat::Json => {
+            let Some(open) = raw.find('{') else {
+                return raw.to_string();
+            };
+            let (head, body) = raw.split_at(open + 1);
+            let rest = body.trim_start();
+            let gap = &body[..body.len() - rest.len()];
+            let entry = format!("\"schema_version\": {CURRENT_SCHEMA_VERSION}");
+            if rest.starts_with('}') {
+                format!("{head}{entry}{body}")
+            } else if gap.contains('\n') {
+                format!("{head}{gap}{entry},{gap}{rest}")
+            } else {
+                format!("{head}{gap}{entry}, {rest}")
+            }
+        }
+        ProfileFormat::Yaml => {
+            let line = format!("schema_version: {CURRENT_SCHEMA_VERSION}\n");
+            // Keep a leading document marker first.
+            match raw.strip_prefix("---") {
+                Some(rest) if rest.starts_with('\n') || rest.starts_with("\r\n") => {
+                    let end = raw.find('\n').map_or(raw.len(), |idx| idx + 1);
+                    format!("{}{line}{}", &raw[..end], &raw[end..])
+                }
+                _ => format!("{line}{raw}"),
+            }
+        }
+        ProfileFormat::Toml => format!("schema_version = {CURRENT_SCHEMA_VERSION}\n{raw}"),
+    }
+}
+
+fn declared_version(value: &Value) -> Option<u32> {
+    value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .map(|version| u32::try_from(version).unwrap_or(u32::MAX))
+}
+
+fn check_version(version: Option<u32>) -> Result<()> {
+    match version {
+        Some(version) if version > CURRENT_SCHEMA_VERSION => bail!(
+            "profile schema_version {version} is newer than this envlock supports ({CURRENT_SCHEMA_VERSION}); run `envlock self-update` to upgrade"
+        ),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unversioned_profiles_stay_strict() {
+        let raw = r#"{"injections":[{"type":"env","ops":[{"op":"prepend","key":"PATH","value":"/bin","separater":":"}]}]}"#;
+        let err = parse(Path::new("legacy.json"), raw, ProfileFormat::Json)
+            .expect_err("unknown key should fail");
+        assert!(err.to_string().contains("unknown field `separater`"));
+    }
+
+    #[test]
+    fn versioned_profiles_stay_strict() {
+        let raw = r#"{"schema_version":1,"injections":[{"type":"symlink","source":"a","target":"b","on_exists":"replace"}]}"#;
+        let err = parse(Path::new("strict.json"), raw, ProfileFormat::Json)
+            .expect_err("unknown key should fail");
+        assert!(err.to_string().contains("unknown field `on_exists`"));
+
+        let raw = r#"{"schema_version":7,"injections":[]}"#;
+        let err = parse(Path::new("future.json"), raw, ProfileFormat::Json)
+            .expect_err("future version should fail");
+        assert!(err.to_string().contains("schema_version 7 is newer"));
+    }
+
+    #[test]
+    fn migrate_stamps_version_and_keeps_comments() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let path = temp.path().join("legacy.yaml");
+        let original =
+            "# team profile\ninjections:\n  - type: env\n    vars:\n      A: \"1\" # kept\n";
+        std::fs::write(&path, original).expect("profile should be written");
+
+        let migration = migrate(&path).expect("migration should succeed");
+        assert!(!migration.is_noop());
+        assert_eq!(migration.migrated, format!("schema_version: 1\n{original}"));
+
+        std::fs::write(&path, &migration.migrated).expect("migrated profile should be written");
+        assert!(migrate(&path).expect("second run should succeed").is_noop());
+    }
+
+    #[test]
+    fn json_stamp_keeps_key_order_and_layout() {
+        let raw = "{\n  \"injections\": [],\n  \"$schema\": \"x\"\n}\n";
+        assert_eq!(
+            stamp_version(raw, ProfileFormat::Json),
+            "{\n  \"schema_version\": 1,\n  \"injections\": [],\n  \"$schema\": \"x\"\n}\n"
+        );
+        assert_eq!(
+            stamp_version(r#"{"injections":[]}"#, ProfileFormat::Json),
+            r#"{"schema_version": 1, "injections":[]}"#
+        );
+        assert_eq!(
+            stamp_version("{}", ProfileFormat::Json),
+            r#"{"schema_version": 1}"#
+        );
+        assert_eq!(
+            stamp_version("---\ninjections: []\n", ProfileFormat::Yaml),
+            "---\nschema_version: 1\ninjections: []\n"
+        );
+    }
+}
@@ -0,0 +1,1035 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use path_absolutize::Absolutize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use super::config::{RawEnv, resolve_envlock_home};
+
+mod canonical;
+mod condition;
+mod diagnostics;
+mod format;
+mod meta;
+mod migrate;
+mod params;
+mod stage;
+
+pub use canonical::{Canonical, canonicalize};
+pub use condition::{Condition, EnvCondition};
+pub use format::{PROFILE_EXTENSIONS, ProfileFormat, find_profile, is_profile_path};
+pub use meta::ProfileMeta;
+pub use migrate::{CURRENT_SCHEMA_VERSION, Migration, migrate};
+pub use params::ParamSpec;
+pub use stage::{find_overlay, list_overlays, validate_stage};
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_cleanup() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+/// An envlock profile: an ordered list of injections plus optional inheritance.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Editor hint pointing at the published JSON Schema; ignored by the loader.
+    #[serde(default, rename = "$schema", skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub schema: Option<String>,
+    /// Profile shape version; omitted means a legacy (version 0) profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub schema_version: Option<u32>,
+    /// Name, description and tags shown by `profiles status` and `preview`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub meta: Option<ProfileMeta>,
+    /// Parent profile: a path relative to this file or a profile name under `envlock_home/profiles`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub extends: Option<String>,
+    /// Parameters referenced as `${param:NAME}` and set with `--set NAME=VALUE`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[schemars(!skip_serializing_if)]
+    pub params: BTreeMap<String, ParamSpec>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(!skip_serializing_if)]
+    pub injections: Vec<InjectionProfile>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum InjectionProfile {
+    Env(EnvProfile),
+    Command(CommandProfile),
+    Symlink(SymlinkProfile),
+    Include(IncludeProfile),
+}
+
+impl InjectionProfile {
+    pub fn enabled(&self) -> bool {
+        match self {
+            Self::Env(spec) => spec.enabled,
+            Self::Command(spec) => spec.enabled,
+            Self::Symlink(spec) => spec.enabled,
+            Self::Include(spec) => spec.enabled,
+        }
+    }
+
+    pub fn when(&self) -> Option<&Condition> {
+        match self {
+            Self::Env(spec) => spec.when.as_ref(),
+            Self::Command(spec) => spec.when.as_ref(),
+            Self::Symlink(spec) => spec.when.as_ref(),
+            Self::Include(spec) => spec.when.as_ref(),
+        }
+    }
+
+    fn when_mut(&mut self) -> &mut Option<Condition> {
+        match self {
+            Self::Env(spec) => &mut spec.when,
+            Self::Command(spec) => &mut spec.when,
+            Self::Symlink(spec) => &mut spec.when,
+            Self::Include(spec) => &mut spec.when,
+        }
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Self::Env(spec) => spec.id.as_deref(),
+            Self::Command(spec) => spec.id.as_deref(),
+            Self::Symlink(spec) => spec.id.as_deref(),
+            Self::Include(_) => None,
+        }
+    }
+
+    pub fn depends_on(&self) -> &[String] {
+        match self {
+            Self::Env(spec) => &spec.depends_on,
+            Self::Command(spec) => &spec.depends_on,
+            Self::Symlink(spec) => &spec.depends_on,
+            Self::Include(_) => &[],
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        match self {
+            Self::Env(spec) => spec.enabled = enabled,
+            Self::Command(spec) => spec.enabled = enabled,
+            Self::Symlink(spec) => spec.enabled = enabled,
+            Self::Include(spec) => spec.enabled = enabled,
+        }
+    }
+
+    /// Profile file the injection was declared in, stamped by [`load`].
+    pub fn origin(&self) -> Option<&Path> {
+        match self {
+            Self::Env(spec) => spec.origin.as_deref(),
+            Self::Command(spec) => spec.origin.as_deref(),
+            Self::Symlink(spec) => spec.origin.as_deref(),
+            Self::Include(_) => None,
+        }
+    }
+
+    fn set_origin(&mut self, origin: &Path) {
+        let slot = match self {
+            Self::Env(spec) => &mut spec.origin,
+            Self::Command(spec) => &mut spec.origin,
+            Self::Symlink(spec) => &mut spec.origin,
+            Self::Include(_) => return,
+        };
+        *slot = Some(origin.to_path_buf());
+    }
+}
+
+/// Exports static variables and applies ordered env operations.
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EnvProfile {
+    #[serde(default = "default_enabled", skip_serializing_if = "is_true")]
+    #[schemars(!skip_serializing_if)]
+    pub enabled: bool,
+    /// Name used by `depends_on`, `--enable`/`--disable` and error messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub id: Option<String>,
+    /// Ids of injections that must run before this one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(!skip_serializing_if)]
+    pub depends_on: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[schemars(!skip_serializing_if)]
+    pub vars: BTreeMap<String, EnvValue>,
+    /// Separator joining list values in `vars`; `"os"` (the default) means `:` on Unix and `;` on Windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub separator: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(!skip_serializing_if)]
+    pub ops: Vec<EnvOpProfile>,
+    /// Accept empty values in `set` and `set_if_absent` ops.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[schemars(!skip_serializing_if)]
+    pub allow_empty: bool,
+    /// Let resource URIs reach outside the resource home through `..` or an
+    /// absolute path.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[schemars(!skip_serializing_if)]
+    pub allow_resource_escape: bool,
+    /// Expand `${VAR}`, `${VAR:-default}`, `${VAR:?message}` and `$$` in
+    /// values against earlier exports and the process environment.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[schemars(!skip_serializing_if)]
+    pub interpolate: bool,
+    /// Skip this injection unless the condition holds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub when: Option<Condition>,
+    #[serde(skip)]
+    pub origin: Option<PathBuf>,
+}
+
+/// Runs a program and imports the `export KEY=value` lines it prints.
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CommandProfile {
+    #[serde(default = "default_enabled", skip_serializing_if = "is_true")]
+    #[schemars(!skip_serializing_if)]
+    pub enabled: bool,
+    /// Name used by `depends_on`, `--enable`/`--disable` and error messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub id: Option<String>,
+    /// Ids of injections that must run before this one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(!skip_serializing_if)]
+    pub depends_on: Vec<String>,
+    pub program: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(!skip_serializing_if)]
+    pub args: Vec<String>,
+    /// Skip this injection unless the condition holds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub when: Option<Condition>,
+    #[serde(skip)]
+    pub origin: Option<PathBuf>,
+}
+
+/// A `vars` value. Numbers and booleans export as written; lists are joined
+/// with the block's `separator`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum EnvValue {
+    String(String),
+    Bool(bool),
+    Number(serde_json::Number),
+    List(Vec<String>),
+}
+
+impl EnvValue {
+    pub fn to_env_string(&self, separator: &str) -> String {
+        match self {
+            Self::String(value) => value.clone(),
+            Self::Bool(value) => value.to_string(),
+            Self::Number(value) => value.to_string(),
+            Self::List(values) => values.join(separator),
+        }
+    }
+}
+
+impl From<&str> for EnvValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for EnvValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+pub enum EnvOpProfile {
+    Set {
+        key: String,
+        value: String,
+    },
+    SetIfAbsent {
+        key: String,
+        value: String,
+    },
+    Prepend {
+        key: String,
+        value: String,
+        /// Segment separator; `"os"` (the default) means `:` on Unix and `;` on Windows.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[schemars(!skip_serializing_if)]
+        separator: Option<String>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        #[schemars(!skip_serializing_if)]
+        dedup: bool,
+        /// Treat the value as a path list and resolve relative segments against the profile directory.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        #[schemars(!skip_serializing_if)]
+        path: bool,
+    },
+    Append {
+        key: String,
+        value: String,
+        /// Segment separator; `"os"` (the default) means `:` on Unix and `;` on Windows.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[schemars(!skip_serializing_if)]
+        separator: Option<String>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        #[schemars(!skip_serializing_if)]
+        dedup: bool,
+        /// Treat the value as a path list and resolve relative segments against the profile directory.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        #[schemars(!skip_serializing_if)]
+        path: bool,
+    },
+    /// Drops list segments matching any segment of `value`, exactly or as a
+    /// `*`/`?` glob.
+    Remove {
+        key: String,
+        value: String,
+        /// Segment separator; `"os"` (the default) means `:` on Unix and `;` on Windows.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[schemars(!skip_serializing_if)]
+        separator: Option<String>,
+    },
+    /// Canonicalizes list segments, drops ones that do not exist and dedups
+    /// by real path.
+    Normalize {
+        key: String,
+        /// Segment separator; `"os"` (the default) means `:` on Unix and `;` on Windows.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[schemars(!skip_serializing_if)]
+        separator: Option<String>,
+    },
+    /// Sets `key` to the empty string.
+    SetEmpty {
+        key: String,
+    },
+    /// Sets `to` to the current value of `from`.
+    Copy {
+        from: String,
+        to: String,
+    },
+    /// Sets `to` to the current value of `from`, then unsets `from`.
+    Move {
+        from: String,
+        to: String,
+    },
+    /// Rewrites every match of the regex `pattern` in the current value;
+    /// `replacement` may use `$1` or `${name}` groups.
+    Replace {
+        key: String,
+        pattern: String,
+        replacement: String,
+    },
+    Unset {
+        key: String,
+    },
+}
+
+impl EnvOpProfile {
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Set { key, .. }
+            | Self::SetIfAbsent { key, .. }
+            | Self::Prepend { key, .. }
+            | Self::Append { key, .. }
+            | Self::Remove { key, .. }
+            | Self::Normalize { key, .. }
+            | Self::Replace { key, .. }
+            | Self::SetEmpty { key }
+            | Self::Unset { key } => key,
+            Self::Copy { to, .. } | Self::Move { to, .. } => to,
+        }
+    }
+}
+
+/// Links `source` to `target` for the lifetime of the session.
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SymlinkProfile {
+    #[serde(default = "default_enabled", skip_serializing_if = "is_true")]
+    #[schemars(!skip_serializing_if)]
+    pub enabled: bool,
+    /// Name used by `depends_on`, `--enable`/`--disable` and error messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub id: Option<String>,
+    /// Ids of injections that must run before this one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(!skip_serializing_if)]
+    pub depends_on: Vec<String>,
+    pub source: PathBuf,
+    pub target: PathBuf,
+    #[serde(default, skip_serializing_if = "SymlinkOnExist::is_default")]
+    #[schemars(!skip_serializing_if)]
+    pub on_exist: SymlinkOnExist,
+    #[serde(default = "default_cleanup", skip_serializing_if = "is_true")]
+    #[schemars(!skip_serializing_if)]
+    pub cleanup: bool,
+    /// Skip this injection unless the condition holds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub when: Option<Condition>,
+    #[serde(skip)]
+    pub origin: Option<PathBuf>,
+}
+
+/// Inlines the injections of another profile file at this position.
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct IncludeProfile {
+    #[serde(default = "default_enabled", skip_serializing_if = "is_true")]
+    #[schemars(!skip_serializing_if)]
+    pub enabled: bool,
+    pub path: PathBuf,
+    /// Combined with the `when` of every included injection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub when: Option<Condition>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SymlinkOnExist {
+    #[default]
+    Error,
+    Replace,
+}
+
+impl SymlinkOnExist {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// JSON Schema describing the profile file shape, generated from the types above.
+pub fn json_schema() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(Profile))
+        .expect("generated profile schema should serialize")
+}
+
+/// Options that influence how a profile tree is resolved.
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    /// Values from `--set NAME=VALUE`, overriding declared defaults.
+    pub params: BTreeMap<String, String>,
+    /// Resolve unset required params to an empty string instead of failing.
+    pub allow_missing_params: bool,
+    /// Append each profile's `<stem>.<stage>.<ext>` overlay after it.
+    pub stage: Option<String>,
+    /// Fail when a profile has no overlay for `stage`.
+    pub require_overlay: bool,
+}
+
+pub fn load(path: &Path) -> Result<Profile> {
+    load_with_options(path, &LoadOptions::default())
+}
+
+pub fn load_with_options(path: &Path, options: &LoadOptions) -> Result<Profile> {
+    load_stack(&[path.to_path_buf()], options)
+}
+
+/// Loads several profiles as one stack: injections are concatenated in order,
+/// params are declared and resolved across the whole stack, and `meta` comes
+/// from the last profile that has one.
+pub fn load_stack(paths: &[PathBuf], options: &LoadOptions) -> Result<Profile> {
+    let mut layers = Vec::with_capacity(paths.len());
+    for path in paths {
+        layers.push(path.clone());
+        let Some(stage) = &options.stage else {
+            continue;
+        };
+        validate_stage(stage)?;
+        match find_overlay(path, stage) {
+            Some(overlay) => layers.push(overlay),
+            None if options.require_overlay => bail!(
+                "no `{stage}` overlay for profile in strict mode: {}",
+                path.display()
+            ),
+            None => debug!(profile = %path.display(), stage, "no stage overlay found"),
+        }
+    }
+
+    let mut stacked: Option<Profile> = None;
+    for path in &layers {
+        let mut chain = Vec::new();
+        let profile = load_chain(path, &mut chain)?;
+        stacked = Some(match stacked {
+            None => profile,
+            Some(mut acc) => {
+                acc.injections.extend(profile.injections);
+                acc.params.extend(profile.params);
+                if profile.meta.is_some() {
+                    acc.meta = profile.meta;
+                }
+                acc
+            }
+        });
+    }
+    let Some(mut profile) = stacked else {
+        bail!("no profile to load");
+    };
+
+    let label = layers
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" + ");
+    let values = params::resolve(
+        &profile.params,
+        &options.params,
+        options.allow_missing_params,
+    )
+    .with_context(|| format!("invalid params for profile: {label}"))?;
+    for injection in &mut profile.injections {
+        params::apply(injection, &values).with_context(|| {
+            format!(
+                "failed to resolve params in profile: {}",
+                injection
+                    .origin()
+                    .map_or_else(|| label.clone(), |origin| origin.display().to_string())
+            )
+        })?;
+        normalize_symlink_paths(injection)?;
+    }
+    Ok(profile)
+}
+
+fn load_chain(path: &Path, chain: &mut Vec<PathBuf>) -> Result<Profile> {
+    let identity = profile_identity(path);
+    if chain.contains(&identity) {
+        chain.push(identity);
+        bail!("profile reference cycle detected: {}", format_chain(chain));
+    }
+    chain.push(identity);
+
+    let mut profile = if chain.len() > 1 {
+        read_profile(path)
+            .with_context(|| format!("failed to load profile chain: {}", format_chain(chain)))?
+    } else {
+        read_profile(path)?
+    };
+    if let Some(meta) = &profile.meta {
+        meta.check_version()
+            .with_context(|| format!("unsupported profile: {}", path.display()))?;
+    }
+    normalize_condition_paths(path, &mut profile)?;
+    let injections = std::mem::take(&mut profile.injections);
+    let own_params = std::mem::take(&mut profile.params);
+    profile.injections = expand_includes(path, injections, &mut profile.params, chain)?;
+    profile.params.extend(own_params);
+
+    if let Some(reference) = profile.extends.take() {
+        let parent_path = resolve_extends(path, &reference)?;
+        let parent = load_chain(&parent_path, chain)?;
+        let mut injections = parent.injections;
+        injections.append(&mut profile.injections);
+        profile.injections = injections;
+        let mut params = parent.params;
+        params.append(&mut profile.params);
+        profile.params = params;
+    }
+
+    chain.pop();
+    Ok(profile)
+}
+
+fn expand_includes(
+    profile_path: &Path,
+    injections: Vec<InjectionProfile>,
+    params: &mut BTreeMap<String, ParamSpec>,
+    chain: &mut Vec<PathBuf>,
+) -> Result<Vec<InjectionProfile>> {
+    let base_dir = profile_path.parent().unwrap_or(Path::new("."));
+    let mut out = Vec::with_capacity(injections.len());
+    for mut injection in injections {
+        match injection {
+            InjectionProfile::Include(spec) => {
+                if !spec.enabled {
+                    continue;
+                }
+                let include_path = normalize_path(&spec.path, base_dir)?;
+                let fragment = load_chain(&include_path, chain)?;
+                params.extend(fragment.params);
+                for mut included in fragment.injections {
+                    if let Some(guard) = &spec.when {
+                        let when = included.when_mut();
+                        *when = Some(match when.take() {
+                            Some(own) => Condition::All(vec![guard.clone(), own]),
+                            None => guard.clone(),
+                        });
+                    }
+                    out.push(included);
+                }
+            }
+            _ => {
+                injection.set_origin(profile_path);
+                out.push(injection);
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn read_profile(path: &Path) -> Result<Profile> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read profile file: {}", path.display()))?;
+    let format = ProfileFormat::from_path(path)?;
+    let profile = migrate::parse(path, &raw, format)?;
+    if profile.schema_version.is_none() {
+        warn!(
+            "profile has no schema_version, which is deprecated; run `envlock profiles migrate --write {}`",
+            path.display()
+        );
+    }
+    Ok(profile)
+}
+
+/// Resolves an `extends` reference: anything that looks like a path is taken
+/// relative to the referencing profile, a bare name is looked up under
+/// `envlock_home/profiles`.
+fn resolve_extends(profile_path: &Path, reference: &str) -> Result<PathBuf> {
+    if reference.trim().is_empty() {
+        bail!(
+            "extends must not be empty in profile: {}",
+            profile_path.display()
+        );
+    }
+    if is_profile_name(reference) {
+        let envlock_home = resolve_envlock_home(&RawEnv::from_process())
+            .context("unable to resolve envlock home for extends lookup")?;
+        let profiles_dir = envlock_home.join("profiles");
+        return Ok(find_profile(&profiles_dir, reference)
+            .unwrap_or_else(|| profiles_dir.join(format!("{reference}.json"))));
+    }
+    let base_dir = profile_path.parent().unwrap_or(Path::new("."));
+    normalize_path(Path::new(reference), base_dir)
+}
+
+fn is_profile_name(reference: &str) -> bool {
+    !reference.contains(['/', '\\'])
+        && !reference.starts_with(['.', '~'])
+        && !is_profile_path(Path::new(reference))
+}
+
+fn profile_identity(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| {
+        path.absolutize()
+            .map(|abs| abs.to_path_buf())
+            .unwrap_or_else(|_| path.to_path_buf())
+    })
+}
+
+fn format_chain(chain: &[PathBuf]) -> String {
+    chain
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+/// Resolves relative symlink paths against the file that declared them. Runs
+/// after param substitution so `${param:...}` may expand to absolute paths.
+fn normalize_symlink_paths(injection: &mut InjectionProfile) -> Result<()> {
+    if let InjectionProfile::Symlink(spec) = injection {
+        let base_dir = spec
+            .origin
+            .as_deref()
+            .and_then(Path::parent)
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        spec.source = normalize_path(&spec.source, &base_dir)?;
+        spec.target = normalize_path(&spec.target, &base_dir)?;
+    }
+    Ok(())
+}
+
+fn normalize_condition_paths(profile_path: &Path, profile: &mut Profile) -> Result<()> {
+    let base_dir = profile_path.parent().unwrap_or(Path::new("."));
+    for injection in &mut profile.injections {
+        if let Some(when) = injection.when_mut() {
+            let mut failure = None;
+            when.paths_mut(&mut |path| match normalize_path(path, base_dir) {
+                Ok(normalized) => *path = normalized,
+                Err(err) => failure = Some(err),
+            });
+            if let Some(err) = failure {
+                return Err(err);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn normalize_path(path: &Path, base_dir: &Path) -> Result<PathBuf> {
+    let raw = path.to_string_lossy();
+    let expanded = shellexpand::tilde(&raw);
+    let expanded_path = PathBuf::from(expanded.as_ref());
+    if expanded_path.is_absolute() {
+        return Ok(expanded_path);
+    }
+    Ok(expanded_path.absolutize_from(base_dir)?.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_var<'a>(env: &'a EnvProfile, key: &str) -> &'a str {
+        match &env.vars[key] {
+            EnvValue::String(value) => value,
+            other => panic!("expected string value, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_injections_with_defaults() {
+        let raw = r#"
+        {
+          "injections": [
+            { "type": "env", "vars": { "A": "1", "B": "2" } },
+            { "type": "symlink", "source": "./fixtures/agents.md", "target": "~/.codex/AGENTS.md" }
+          ]
+        }"#;
+
+        let profile: Profile = serde_json::from_str(raw).expect("profile should parse");
+        assert_eq!(profile.injections.len(), 2);
+
+        match &profile.injections[0] {
+            InjectionProfile::Env(env) => {
+                assert!(env.enabled);
+                assert_eq!(env.vars.get("A"), Some(&"1".into()));
+                assert_eq!(env.vars.get("B"), Some(&"2".into()));
+                assert!(env.ops.is_empty());
+            }
+            _ => panic!("expected env injection"),
+        }
+        match &profile.injections[1] {
+            InjectionProfile::Symlink(link) => {
+                assert!(link.enabled);
+                assert!(matches!(link.on_exist, SymlinkOnExist::Error));
+                assert!(link.cleanup);
+            }
+            _ => panic!("expected symlink injection"),
+        }
+    }
+
+    #[test]
+    fn schema_key_is_accepted_and_ignored() {
+        let raw = r#"
+        {
+          "$schema": "https://perishcode.github.io/envlock/profile.schema.json",
+          "injections": [
+            { "type": "env", "vars": { "A": "1" } }
+          ]
+        }"#;
+
+        let profile: Profile = serde_json::from_str(raw).expect("profile should parse");
+        assert_eq!(profile.injections.len(), 1);
+    }
+
+    #[test]
+    fn reject_unknown_injection_type() {
+        let raw = r#"
+        {
+          "injections": [
+            { "type": "python" }
+          ]
+        }"#;
+
+        let err = serde_json::from_str::<Profile>(raw).expect_err("unknown type should fail");
+        let msg = err.to_string();
+        assert!(msg.contains("unknown variant"));
+    }
+
+    #[test]
+    fn parse_env_ops() {
+        let raw = r#"
+        {
+          "injections": [
+            {
+              "type": "env",
+              "vars": { "A": "1" },
+              "ops": [
+                { "op": "prepend", "key": "PATH", "value": "/opt/bin", "separator": "os", "dedup": true },
+                { "op": "set_if_absent", "key": "NPM_CONFIG_REGISTRY", "value": "https://registry.npmjs.org/" }
+              ]
+            }
+          ]
+        }"#;
+
+        let profile: Profile = serde_json::from_str(raw).expect("profile should parse");
+        match &profile.injections[0] {
+            InjectionProfile::Env(env) => {
+                assert_eq!(env.vars.get("A"), Some(&"1".into()));
+                assert_eq!(env.ops.len(), 2);
+            }
+            _ => panic!("expected env injection"),
+        }
+    }
+
+    #[test]
+    fn parse_command_injection() {
+        let raw = r#"
+        {
+          "injections": [
+            { "type": "command", "program": "fnm", "args": ["env", "--shell", "bash"] }
+          ]
+        }"#;
+
+        let profile: Profile = serde_json::from_str(raw).expect("profile should parse");
+        match &profile.injections[0] {
+            InjectionProfile::Command(cmd) => {
+                assert!(cmd.enabled);
+                assert_eq!(cmd.program, "fnm");
+                assert_eq!(cmd.args, vec!["env", "--shell", "bash"]);
+            }
+            _ => panic!("expected command injection"),
+        }
+    }
+
+    #[test]
+    fn extends_puts_parent_injections_first() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        std::fs::write(
+            temp.path().join("base.json"),
+            r#"{"injections":[{"type":"env","vars":{"FROM":"base"}}]}"#,
+        )
+        .expect("base profile should be written");
+        let child = temp.path().join("child.json");
+        std::fs::write(
+            &child,
+            r#"{"extends":"./base.json","injections":[{"type":"env","vars":{"FROM":"child"}}]}"#,
+        )
+        .expect("child profile should be written");
+
+        let profile = load(&child).expect("child profile should load");
+        let values: Vec<&str> = profile
+            .injections
+            .iter()
+            .map(|injection| match injection {
+                InjectionProfile::Env(env) => string_var(env, "FROM"),
+                _ => panic!("expected env injection"),
+            })
+            .collect();
+        assert_eq!(values, vec!["base", "child"]);
+    }
+
+    #[test]
+    fn extends_normalizes_symlinks_against_each_file_dir() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let shared = temp.path().join("shared");
+        std::fs::create_dir_all(&shared).expect("shared dir should exist");
+        std::fs::write(
+            shared.join("base.json"),
+            r#"{"injections":[{"type":"symlink","source":"./agents.md","target":"./out.md"}]}"#,
+        )
+        .expect("base profile should be written");
+        let child = temp.path().join("child.json");
+        std::fs::write(&child, r#"{"extends":"shared/base.json"}"#)
+            .expect("child profile should be written");
+
+        let profile = load(&child).expect("child profile should load");
+        match &profile.injections[0] {
+            InjectionProfile::Symlink(link) => {
+                assert_eq!(link.source, shared.join("agents.md"));
+                assert_eq!(link.target, shared.join("out.md"));
+            }
+            _ => panic!("expected symlink injection"),
+        }
+    }
+
+    #[test]
+    fn extends_cycle_reports_full_chain() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        std::fs::write(temp.path().join("a.json"), r#"{"extends":"./b.json"}"#)
+            .expect("profile a should be written");
+        std::fs::write(temp.path().join("b.json"), r#"{"extends":"./a.json"}"#)
+            .expect("profile b should be written");
+
+        let err = load(&temp.path().join("a.json")).expect_err("cycle should fail");
+        let msg = format!("{err:#}");
+        assert!(msg.contains("profile reference cycle detected"));
+        assert!(msg.contains("a.json -> "));
+        assert!(msg.contains("b.json -> "));
+    }
+
+    #[test]
+    fn include_expands_fragment_in_place() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let fragments = temp.path().join("fragments");
+        std::fs::create_dir_all(&fragments).expect("fragments dir should exist");
+        let kube = fragments.join("kube.json");
+        std::fs::write(
+            &kube,
+            r#"{"injections":[{"type":"env","vars":{"FROM":"kube"}}]}"#,
+        )
+        .expect("fragment should be written");
+        let main = temp.path().join("main.json");
+        std::fs::write(
+            &main,
+            r#"{"injections":[
+              {"type":"env","vars":{"FROM":"before"}},
+              {"type":"include","path":"./fragments/kube.json"},
+              {"type":"include","path":"./fragments/missing.json","enabled":false},
+              {"type":"env","vars":{"FROM":"after"}}
+            ]}"#,
+        )
+        .expect("main profile should be written");
+
+        let profile = load(&main).expect("profile with include should load");
+        let values: Vec<(&str, &Path)> = profile
+            .injections
+            .iter()
+            .map(|injection| match injection {
+                InjectionProfile::Env(env) => (
+                    string_var(env, "FROM"),
+                    injection.origin().expect("origin should be stamped"),
+                ),
+                _ => panic!("expected env injection"),
+            })
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                ("before", main.as_path()),
+                ("kube", kube.as_path()),
+                ("after", main.as_path())
+            ]
+        );
+    }
+
+    #[test]
+    fn include_cycle_is_rejected() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        std::fs::write(
+            temp.path().join("a.json"),
+            r#"{"injections":[{"type":"include","path":"./b.json"}]}"#,
+        )
+        .expect("fragment a should be written");
+        std::fs::write(
+            temp.path().join("b.json"),
+            r#"{"injections":[{"type":"include","path":"./a.json"}]}"#,
+        )
+        .expect("fragment b should be written");
+
+        let err = load(&temp.path().join("a.json")).expect_err("include cycle should fail");
+        assert!(format!("{err:#}").contains("profile reference cycle detected"));
+    }
+
+    #[test]
+    fn load_yaml_and_toml_profiles() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let yaml = temp.path().join("team.yaml");
+        std::fs::write(
+            &yaml,
+            "# shared team profile\nextends: ./base.toml\ninjections:\n  - type: env\n    vars:\n      FROM: yaml\n",
+        )
+        .expect("yaml profile should be written");
+        std::fs::write(
+            temp.path().join("base.toml"),
+            "# base profile\n[[injections]]\ntype = \"env\"\n\n[injections.vars]\nFROM = \"toml\"\n",
+        )
+        .expect("toml profile should be written");
+
+        let profile = load(&yaml).expect("yaml profile should load");
+        let values: Vec<&str> = profile
+            .injections
+            .iter()
+            .map(|injection| match injection {
+                InjectionProfile::Env(env) => string_var(env, "FROM"),
+                _ => panic!("expected env injection"),
+            })
+            .collect();
+        assert_eq!(values, vec!["toml", "yaml"]);
+    }
+
+    #[test]
+    fn include_when_guards_every_included_injection() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let fragments = temp.path().join("fragments");
+        std::fs::create_dir_all(&fragments).expect("fragments dir should exist");
+        std::fs::write(
+            fragments.join("ci.json"),
+            r#"{"injections":[{"type":"env","vars":{"A":"1"},"when":{"file_exists":"./ci.flag"}}]}"#,
+        )
+        .expect("fragment should be written");
+        let main = temp.path().join("main.json");
+        std::fs::write(
+            &main,
+            r#"{"injections":[{"type":"include","path":"./fragments/ci.json","when":{"env":{"key":"CI"}}}]}"#,
+        )
+        .expect("main profile should be written");
+
+        let profile = load(&main).expect("profile should load");
+        let when = profile.injections[0]
+            .when()
+            .expect("when should be combined");
+        assert_eq!(
+            when.describe(),
+            format!(
+                "all(env CI is set, file_exists {})",
+                fragments.join("ci.flag").display()
+            )
+        );
+    }
+
+    #[test]
+    fn params_merge_across_extends_and_substitute_before_normalization() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        let base = temp.path().join("base.json");
+        std::fs::write(
+            &base,
+            r#"{"params":{"stage":{"default":"dev"},"dir":{"default":"shared"}},"injections":[{"type":"symlink","source":"./${param:dir}/a.md","target":"/tmp/${param:stage}.md"}]}"#,
+        )
+        .expect("base profile should be written");
+        let child = temp.path().join("child.json");
+        std::fs::write(
+            &child,
+            r#"{"extends":"./base.json","params":{"stage":{"required":true}},"injections":[{"type":"env","vars":{"STAGE":"${param:stage}"}}]}"#,
+        )
+        .expect("child profile should be written");
+
+        let err = load(&child).expect_err("required param should be enforced");
+        assert!(format!("{err:#}").contains("missing required param `stage`"));
+
+        let options = LoadOptions {
+            params: BTreeMap::from([("stage".to_string(), "prod".to_string())]),
+            ..LoadOptions::default()
+        };
+        let profile = load_with_options(&child, &options).expect("profile should load");
+        match &profile.injections[0] {
+            InjectionProfile::Symlink(spec) => {
+                assert_eq!(spec.source, temp.path().join("shared/a.md"));
+                assert_eq!(spec.target, PathBuf::from("/tmp/prod.md"));
+            }
+            other => panic!("unexpected injection: {other:?}"),
+        }
+        match &profile.injections[1] {
+            InjectionProfile::Env(spec) => assert_eq!(spec.vars["STAGE"], "prod".into()),
+            other => panic!("unexpected injection: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::{Result, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{EnvOpProfile, EnvValue, InjectionProfile};
+
+const REFERENCE_PREFIX: &str = "${param:";
+
+/// Declares a profile parameter that can be overridden with `--set NAME=VALUE`.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ParamSpec {
+    /// Value used when the parameter is not set on the command line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub default: Option<String>,
+    /// Fail the load unless a value is provided with `--set`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[schemars(!skip_serializing_if)]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(!skip_serializing_if)]
+    pub description: Option<String>,
+}
+
+/// Resolves the final value of every declared parameter.
+///
+/// A provided value wins over the declared default. Providing an undeclared
+/// name is an error, as is leaving a required parameter unset unless
+/// `allow_missing` is true (missing values then resolve to an empty string).
+pub(super) fn resolve(
+    declared: &BTreeMap<String, ParamSpec>,
+    provided: &BTreeMap<String, String>,
+    allow_missing: bool,
+) -> Result<BTreeMap<String, String>> {
+    if let Some(name) = provided.keys().find(|name| !declared.contains_key(*name)) {
+        bail!(
+            "unknown param `{name}` (declared: {})",
+            declared_names(declared)
+        );
+    }
+
+    let mut values = BTreeMap::new();
+    for (name, spec) in declared {
+        let value = match (provided.get(name), &spec.default) {
+            (Some(value), _) => value.clone(),
+            (None, _) if spec.required && !allow_missing => {
+                bail!("missing required param `{name}` (pass --set {name}=<value>)")
+            }
+            (None, Some(default)) => default.clone(),
+            (None, None) => String::new(),
+        };
+        values.insert(name.clone(), value);
+    }
+    Ok(values)
+}
+
+/// Replaces `${param:NAME}` references in every field that accepts them.
+pub(super) fn apply(
+    injection: &mut InjectionProfile,
+    values: &BTreeMap<String, String>,
+) -> Result<()> {
+    match injection {
+        InjectionProfile::Env(spec) => {
+            for value in spec.vars.values_mut() {
+                match value {
+                    EnvValue::String(value) => *value = substitute(value, values)?,
+                    EnvValue::List(items) => {
+                        for item in items {
+                            *item = substitute(item, values)?;
+                        }
+                    }
+                    EnvValue::Bool(_) | EnvValue::Number(_) => {}
+                }
+            }
+            for op in &mut spec.ops {
+                match op {
+                    EnvOpProfile::Set { value, .. }
+                    | EnvOpProfile::SetIfAbsent { value, .. }
+                    | EnvOpProfile::Prepend { value, .. }
+                    | EnvOpProfile::Append { value, .. }
+                    | EnvOpProfile::Remove { value, .. }
+                    | EnvOpProfile::Replace {
+                        replacement: value, ..
+                    } => *value = substitute(value, values)?,
+                    EnvOpProfile::Normalize { .. }
+                    | EnvOpProfile::Copy { .. }
+                    | EnvOpProfile::Move { .. }
+                    | EnvOpProfile::SetEmpty { .. }
+                    | EnvOpProfile::Unset { .. } => {}
+                }
+            }
+        }
+        InjectionProfile::Command(spec) => {
+            for arg in &mut spec.args {
+                *arg = substitute(arg, values)?;
+            }
+        }
+        InjectionProfile::Symlink(spec) => {
+            spec.source = substitute_path(&spec.source, values)?;
+            spec.target = substitute_path(&spec.target, values)?;
+        }
+        InjectionProfile::Include(_) => {}
+    }
+    Ok(())
+}
+
+fn substitute_path(path: &std::path::Path, values: &BTreeMap<String, String>) -> Result<PathBuf> {
+    Ok(PathBuf::from(substitute(&path.to_string_lossy(), values)?))
+}
+
+pub(super) fn substitute(input: &str, values: &BTreeMap<String, String>) -> Result<String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find(REFERENCE_PREFIX) {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + REFERENCE_PREFIX.len()..];
+        let Some(end) = after.find('}') else {
+            bail!("unterminated param reference in `{input}`");
+        };
+        let name = &after[..end];
+        let Some(value) = values.get(name) else {
+            bail!(
+                "undeclared param `{name}` referenced in `{input}` (declared: {})",
+                declared_names(values)
+            );
+        };
+        out.push_str(value);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn declared_names<V>(declared: &BTreeMap<String, V>) -> String {
+    if declared.is_empty() {
+        return "none".to_string();
+    }
+    declared.keys().cloned().collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(default: Option<&str>, required: bool) -> ParamSpec {
+        ParamSpec {
+            default: default.map(str::to_string),
+            required,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn provided_value_overrides_default() {
+        let declared = BTreeMap::from([
+            ("region".to_string(), spec(Some("eu"), false)),
+            ("stage".to_string(), spec(Some("dev"), false)),
+        ]);
+        let provided = BTreeMap::from([("stage".to_string(), "prod".to_string())]);
+
+        let values = resolve(&declared, &provided, false).expect("params should resolve");
+        assert_eq!(values["region"], "eu");
+        assert_eq!(values["stage"], "prod");
+    }
+
+    #[test]
+    fn missing_required_and_unknown_params_fail() {
+        let declared = BTreeMap::from([("stage".to_string(), spec(None, true))]);
+
+        let err = resolve(&declared, &BTreeMap::new(), false).expect_err("required param");
+        assert!(err.to_string().contains("missing required param `stage`"));
+        let values = resolve(&declared, &BTreeMap::new(), true).expect("missing allowed");
+        assert_eq!(values["stage"], "");
+
+        let provided = BTreeMap::from([("stag".to_string(), "prod".to_string())]);
+        let err = resolve(&declared, &provided, false).expect_err("unknown param");
+        assert!(err.to_string().contains("unknown param `stag`"));
+    }
+
+    #[test]
+    fn substitute_replaces_references() {
+        let values = BTreeMap::from([("stage".to_string(), "prod".to_string())]);
+        assert_eq!(
+            substitute("api-${param:stage}.${param:stage}", &values).expect("substitute"),
+            "api-prod.prod"
+        );
+        assert_eq!(substitute("${HOME}", &values).expect("plain"), "${HOME}");
+
+        let err = substitute("${param:region}", &values).expect_err("undeclared");
+        assert!(err.to_string().contains("undeclared param `region`"));
+    }
+}
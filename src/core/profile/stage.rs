@@ -0,0 +1,87 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+
+use super::format::{ProfileFormat, find_profile};
+
+/// Stage names become part of a file name, so keep them to one plain segment.
+pub fn validate_stage(stage: &str) -> Result<()> {
+    let valid = !stage.is_empty()
+        && stage
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+    if !valid {
+        bail!("invalid stage `{stage}`: use letters, digits, `-` or `_`");
+    }
+    Ok(())
+}
+
+/// Returns the `<stem>.<stage>.<ext>` overlay next to `profile_path`, in any
+/// supported format.
+pub fn find_overlay(profile_path: &Path, stage: &str) -> Option<PathBuf> {
+    let stem = profile_path.file_stem()?.to_str()?;
+    let dir = profile_path.parent().unwrap_or(Path::new("."));
+    find_profile(dir, &format!("{stem}.{stage}"))
+}
+
+/// Lists every stage overlay next to `profile_path` as `(stage, path)`, sorted
+/// by stage.
+pub fn list_overlays(profile_path: &Path) -> Vec<(String, PathBuf)> {
+    let Some(stem) = profile_path.file_stem().and_then(|stem| stem.to_str()) else {
+        return Vec::new();
+    };
+    let dir = profile_path.parent().unwrap_or(Path::new("."));
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut overlays: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?;
+            let (rest, ext) = name.rsplit_once('.')?;
+            ProfileFormat::from_extension(ext)?;
+            let stage = rest.strip_prefix(stem)?.strip_prefix('.')?;
+            if stage.is_empty() || stage.contains('.') {
+                return None;
+            }
+            Some((stage.to_string(), path))
+        })
+        .collect();
+    overlays.sort();
+    overlays
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlays_match_stem_and_single_stage_segment() {
+        let temp = tempfile::tempdir().expect("temp dir should exist");
+        for name in [
+            "app.json",
+            "app.ci.json",
+            "app.prod.yaml",
+            "app.ci.local.json",
+            "apps.ci.json",
+            "app.ci.txt",
+        ] {
+            std::fs::write(temp.path().join(name), "{}").expect("file should be written");
+        }
+        let base = temp.path().join("app.json");
+
+        let stages: Vec<String> = list_overlays(&base)
+            .into_iter()
+            .map(|(stage, _)| stage)
+            .collect();
+        assert_eq!(stages, vec!["ci", "prod"]);
+        assert_eq!(
+            find_overlay(&base, "prod"),
+            Some(temp.path().join("app.prod.yaml"))
+        );
+        assert_eq!(find_overlay(&base, "qa"), None);
+        assert!(validate_stage("ci-2").is_ok());
+        assert!(validate_stage("../ci").is_err());
+    }
+}
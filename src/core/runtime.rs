@@ -6,7 +6,8 @@ use tracing::{debug, info};
 use super::app::AppContext;
 use super::config::OutputMode;
 use super::env_key::is_valid_env_key;
-use super::{injections, profile};
+use super::injections::{self, Export};
+use super::profile::{self, LoadOptions};
 
 pub struct RunResult {
     pub exit_code: Option<i32>,
@@ -15,16 +16,32 @@ pub struct RunResult {
 pub fn run(app: &dyn AppContext) -> Result<RunResult> {
     let config = app.config();
     info!(
-        profile_path = %config.profile_path.display(),
+        profile_paths = %config
+            .profile_paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        profile_source = config.profile_source.as_str(),
         output_mode = match config.output_mode {
             OutputMode::Shell => "shell",
             OutputMode::Json => "json",
         },
         strict = config.strict,
+        stage = config.stage.as_deref().unwrap_or(""),
         has_command = config.command.is_some(),
         "envlock run started"
     );
-    let profile = profile::load(&config.profile_path).context("unable to load envlock profile")?;
+    let profile = profile::load_stack(
+        &config.profile_paths,
+        &LoadOptions {
+            params: config.params.clone(),
+            stage: config.stage.clone(),
+            require_overlay: config.strict,
+            ..LoadOptions::default()
+        },
+    )
+    .context("unable to load envlock profile")?;
     let run_result = injections::with_registered_exports(app, profile.injections, |exports| {
         info!(
             export_count = exports.len(),
@@ -32,7 +49,7 @@ pub fn run(app: &dyn AppContext) -> Result<RunResult> {
         );
         let env = to_env_map(exports.to_vec(), config.strict)?;
         if let Some(command) = &config.command {
-            let run_exports: Vec<(String, String)> = env.into_iter().collect();
+            let run_exports: Vec<Export> = env.into_iter().collect();
             let code = run_command(command, &run_exports)?;
             return Ok(RunResult {
                 exit_code: Some(code),
@@ -45,7 +62,8 @@ pub fn run(app: &dyn AppContext) -> Result<RunResult> {
     Ok(run_result)
 }
 
-fn print_outputs(env: BTreeMap<String, String>, mode: OutputMode) -> Result<()> {
+/// Shell mode emits `unset KEY` for unset keys; JSON mode maps them to `null`.
+fn print_outputs(env: BTreeMap<String, Option<String>>, mode: OutputMode) -> Result<()> {
     debug!(
         output_mode = match mode {
             OutputMode::Json => "json",
@@ -57,14 +75,19 @@ fn print_outputs(env: BTreeMap<String, String>, mode: OutputMode) -> Result<()>
         OutputMode::Json => println!("{}", serde_json::to_string_pretty(&env)?),
         OutputMode::Shell => {
             for (key, value) in env {
-                println!("export {}='{}'", key, shell_single_quote_escape(&value));
+                match value {
+                    Some(value) => {
+                        println!("export {}='{}'", key, shell_single_quote_escape(&value))
+                    }
+                    None => println!("unset {}", key),
+                }
             }
         }
     }
     Ok(())
 }
 
-fn to_env_map(exports: Vec<(String, String)>, strict: bool) -> Result<BTreeMap<String, String>> {
+fn to_env_map(exports: Vec<Export>, strict: bool) -> Result<BTreeMap<String, Option<String>>> {
     let mut env = BTreeMap::new();
     for (key, value) in exports {
         if !is_valid_env_key(&key) {
@@ -82,7 +105,7 @@ fn shell_single_quote_escape(input: &str) -> String {
     input.replace('\'', "'\"'\"'")
 }
 
-fn run_command(command: &[String], exports: &[(String, String)]) -> Result<i32> {
+fn run_command(command: &[String], exports: &[Export]) -> Result<i32> {
     if command.is_empty() {
         bail!("command mode requires at least one command token");
     }
@@ -91,7 +114,12 @@ fn run_command(command: &[String], exports: &[(String, String)]) -> Result<i32>
     if command.len() > 1 {
         child.args(&command[1..]);
     }
-    child.envs(exports.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+    for (key, value) in exports {
+        match value {
+            Some(value) => child.env(key, value),
+            None => child.env_remove(key),
+        };
+    }
 
     let status = child.status().context("failed to execute child command")?;
     if let Some(code) = status.code() {
@@ -122,23 +150,24 @@ mod tests {
     fn env_map_keeps_last_value_for_duplicate_keys() {
         let map = to_env_map(
             vec![
-                ("A".to_string(), "1".to_string()),
-                ("B".to_string(), "2".to_string()),
-                ("A".to_string(), "3".to_string()),
+                ("A".to_string(), Some("1".to_string())),
+                ("B".to_string(), Some("2".to_string())),
+                ("A".to_string(), Some("3".to_string())),
+                ("B".to_string(), None),
             ],
             false,
         )
         .expect("non-strict mode should allow duplicate keys");
-        assert_eq!(map.get("A"), Some(&"3".to_string()));
-        assert_eq!(map.get("B"), Some(&"2".to_string()));
+        assert_eq!(map.get("A"), Some(&Some("3".to_string())));
+        assert_eq!(map.get("B"), Some(&None));
     }
 
     #[test]
     fn env_map_rejects_duplicate_keys_in_strict_mode() {
         let err = to_env_map(
             vec![
-                ("A".to_string(), "1".to_string()),
-                ("A".to_string(), "2".to_string()),
+                ("A".to_string(), Some("1".to_string())),
+                ("A".to_string(), Some("2".to_string())),
             ],
             true,
         )
@@ -148,7 +177,7 @@ mod tests {
 
     #[test]
     fn env_map_rejects_invalid_key() {
-        let err = to_env_map(vec![("BAD-KEY".to_string(), "1".to_string())], false)
+        let err = to_env_map(vec![("BAD-KEY".to_string(), Some("1".to_string()))], false)
             .expect_err("invalid env key should fail");
         assert!(err.to_string().contains("invalid exported key"));
     }
@@ -56,10 +56,10 @@ pub fn run_plugin(options: PluginHostOptions) -> Result<()> {
     let envlock_home = resolve_envlock_home(&RawEnv::from_process())?;
     let script_path = plugin_script_path(&envlock_home, &options.plugin);
 
-    if options.method == "init" {
-        if let Some(script) = builtin_plugin_script(&options.plugin) {
-            install_plugin_script(&script_path, script, options.force_install)?;
-        }
+    if options.method == "init"
+        && let Some(script) = builtin_plugin_script(&options.plugin)
+    {
+        install_plugin_script(&script_path, script, options.force_install)?;
     }
 
     if !script_path.is_file() {
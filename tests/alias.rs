@@ -1,7 +1,31 @@
-use std::process::Command;
+use std::path::Path;
+use std::process::{Command, Output};
 
 use tempfile::TempDir;
 
+/// Registers `profile` as alias `name` under `envlock_home`.
+fn append_alias(envlock_home: &Path, name: &str, profile: &str) {
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["alias", "append", name, "--profile", profile])
+        .env("ENVLOCK_HOME", envlock_home)
+        .output()
+        .expect("envlock command should run");
+    assert!(
+        output.status.success(),
+        "alias append failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn alias_run(envlock_home: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["alias", "run"])
+        .args(args)
+        .env("ENVLOCK_HOME", envlock_home)
+        .output()
+        .expect("envlock command should run")
+}
+
 #[test]
 fn alias_append_and_list_work() {
     let temp = TempDir::new().expect("temp dir should be created");
@@ -159,3 +183,78 @@ fn bare_alias_name_is_not_supported() {
     let stderr = String::from_utf8(run.stderr).expect("stderr should be UTF-8");
     assert!(stderr.contains("failed to execute child command"));
 }
+
+#[test]
+fn alias_run_accepts_set() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let envlock_home = temp.path().join("envlock-home");
+    let profile = temp.path().join("params.json");
+    std::fs::write(
+        &profile,
+        r#"{"params":{"stage":{"required":true}},"injections":[{"type":"env","vars":{"API_URL":"https://${param:stage}.example.com"}}]}"#,
+    )
+    .expect("profile should be written");
+    append_alias(
+        &envlock_home,
+        "svc",
+        profile.to_str().expect("path should be UTF-8"),
+    );
+
+    let output = alias_run(
+        &envlock_home,
+        &[
+            "svc",
+            "--set",
+            "stage=qa",
+            "--",
+            "sh",
+            "-c",
+            "printf '%s' \"$API_URL\"",
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "alias run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "https://qa.example.com"
+    );
+}
+
+#[test]
+fn alias_run_accepts_toggles() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let envlock_home = temp.path().join("envlock-home");
+    let profile = temp.path().join("ids.json");
+    std::fs::write(
+        &profile,
+        r#"{"injections":[{"type":"env","id":"debug","enabled":false,"vars":{"DEBUG":"1"}}]}"#,
+    )
+    .expect("profile should be written");
+    append_alias(
+        &envlock_home,
+        "ids",
+        profile.to_str().expect("path should be UTF-8"),
+    );
+
+    let output = alias_run(
+        &envlock_home,
+        &[
+            "ids",
+            "--enable",
+            "debug",
+            "--",
+            "sh",
+            "-c",
+            "printf '%s' \"$DEBUG\"",
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "alias run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1");
+}
@@ -171,3 +171,87 @@ fn command_mode_honors_strict_duplicate_key_checks() {
     let stderr = String::from_utf8(output.stderr).expect("stderr should be UTF-8");
     assert!(stderr.contains("duplicate exported key"));
 }
+
+#[test]
+fn command_mode_removes_unset_keys_from_child_env() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = temp.path().join("unset-profile.json");
+    std::fs::write(
+        &profile,
+        r#"{"injections":[{"type":"env","ops":[{"op":"unset","key":"ENVLOCK_TEST_SECRET"}]}]}"#,
+    )
+    .expect("profile should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args([
+            "-p",
+            profile
+                .to_str()
+                .expect("profile path should be valid UTF-8"),
+            "--log-level",
+            "error",
+            "--",
+            "bash",
+            "-lc",
+            "printf '%s' \"${ENVLOCK_TEST_SECRET-gone}\"",
+        ])
+        .env("ENVLOCK_TEST_SECRET", "leaked")
+        .output()
+        .expect("envlock command should run");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert_eq!(stdout, "gone");
+}
+
+#[test]
+fn invalid_keys_are_reported_together_before_anything_runs() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let root = temp.path();
+    let marker = root.join("command-ran");
+    let source = root.join("source.txt");
+    let target = root.join("link.txt");
+    std::fs::write(&source, "x").expect("source should be written");
+    let profile = root.join("broken.json");
+    std::fs::write(
+        &profile,
+        format!(
+            r#"{{"injections":[
+                {{"type":"command","program":"touch","args":["{marker}"]}},
+                {{"type":"symlink","source":"{source}","target":"{target}"}},
+                {{"type":"env","id":"proxy","vars":{{"BAD-KEY":"1"}},"ops":[
+                    {{"op":"set","key":"1ST","value":"x"}},
+                    {{"op":"copy","from":"OK","to":"ALSO BAD"}}
+                ]}},
+                {{"type":"symlink","source":"{missing}","target":"{target}"}}
+            ]}}"#,
+            marker = marker.display(),
+            source = source.display(),
+            target = target.display(),
+            missing = root.join("missing").display(),
+        ),
+    )
+    .expect("profile should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .arg("-p")
+        .arg(&profile)
+        .output()
+        .expect("envlock should run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for problem in [
+        "validation failed with 4 problem(s)",
+        "env `proxy`: invalid env var key `BAD-KEY`",
+        "env `proxy`: invalid env var key `1ST`",
+        "env `proxy`: invalid env var key `ALSO BAD`",
+        "symlink: source does not exist",
+    ] {
+        assert!(stderr.contains(problem), "missing `{problem}` in {stderr}");
+    }
+    assert!(!marker.exists(), "command should not run");
+    assert!(
+        std::fs::symlink_metadata(&target).is_err(),
+        "symlink should not be created"
+    );
+}
@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use tempfile::TempDir;
+
+pub fn write_profile(temp: &TempDir, name: &str, raw: &str) -> PathBuf {
+    let profile = temp.path().join(name);
+    std::fs::write(&profile, raw).expect("profile should be written");
+    profile
+}
+
+/// envlock isolated from the caller's home, stage and log settings.
+pub fn envlock(temp: &TempDir) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_envlock"));
+    command
+        .env("HOME", temp.path())
+        .env_remove("ENVLOCK_HOME")
+        .env_remove("ENVLOCK_STAGE")
+        .env_remove("RUST_LOG");
+    command
+}
@@ -82,3 +82,59 @@ fn fails_when_default_profile_missing() {
     let stderr = String::from_utf8(output.stderr).expect("stderr should be UTF-8");
     assert!(stderr.contains("profiles/default.json"));
 }
+
+#[test]
+fn discovers_project_profile_before_default() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let envlock_home = temp.path().join("envlock-home");
+    let profiles_dir = envlock_home.join("profiles");
+    std::fs::create_dir_all(&profiles_dir).expect("profiles dir should be created");
+    std::fs::write(
+        profiles_dir.join("default.json"),
+        r#"{"injections":[{"type":"env","vars":{"ENVLOCK_PROFILE":"from-default"}}]}"#,
+    )
+    .expect("default profile should be written");
+
+    let repo = temp.path().join("repo");
+    let nested = repo.join("src/deep");
+    std::fs::create_dir_all(&nested).expect("nested dir should be created");
+    std::fs::write(
+        repo.join(".envlock.json"),
+        r#"{"injections":[{"type":"env","vars":{"ENVLOCK_PROFILE":"from-project"}}]}"#,
+    )
+    .expect("project profile should be written");
+
+    let run = |no_discovery: bool| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_envlock"));
+        command
+            .args(["--output", "json", "--log-level", "error"])
+            .current_dir(&nested)
+            .env("HOME", temp.path())
+            .env("ENVLOCK_HOME", &envlock_home);
+        if no_discovery {
+            command.env("ENVLOCK_NO_DISCOVERY", "1");
+        } else {
+            command.env_remove("ENVLOCK_NO_DISCOVERY");
+        }
+        let output = command.output().expect("envlock command should run");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).expect("stdout should be UTF-8")
+    };
+
+    assert!(run(false).contains("\"ENVLOCK_PROFILE\": \"from-project\""));
+    assert!(run(true).contains("\"ENVLOCK_PROFILE\": \"from-default\""));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "status"])
+        .current_dir(&nested)
+        .env("HOME", temp.path())
+        .env("ENVLOCK_HOME", &envlock_home)
+        .env_remove("ENVLOCK_NO_DISCOVERY")
+        .output()
+        .expect("envlock command should run");
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(stdout.contains(&format!(
+        "active_profile: {} (discovered)",
+        repo.join(".envlock.json").display()
+    )));
+}
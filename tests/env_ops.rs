@@ -0,0 +1,150 @@
+use serde_json::Value;
+use tempfile::TempDir;
+
+mod common;
+
+use common::{envlock, write_profile};
+
+#[test]
+fn unset_keys_reach_shell_and_json_output() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = write_profile(
+        &temp,
+        "unset.json",
+        r#"{"injections":[
+    {"type":"env","vars":{"KEEP":"1"},"ops":[{"op":"unset","key":"ENVLOCK_TEST_SECRET"}]}
+]}"#,
+    );
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .env("ENVLOCK_TEST_SECRET", "leaked")
+        .output()
+        .expect("envlock command should run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert_eq!(stdout, "unset ENVLOCK_TEST_SECRET\nexport KEEP='1'\n");
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .args(["--output", "json"])
+        .env("ENVLOCK_TEST_SECRET", "leaked")
+        .output()
+        .expect("envlock command should run");
+    let json: Value = serde_json::from_slice(&output.stdout).expect("output should be JSON");
+    assert_eq!(json["KEEP"], "1");
+    assert!(json["ENVLOCK_TEST_SECRET"].is_null());
+}
+
+#[test]
+fn copy_move_and_replace_read_earlier_values_and_process_env() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = write_profile(
+        &temp,
+        "ops.json",
+        r#"{"injections":[
+    {"type":"env","vars":{"REGISTRY_URL":"https://registry.old.example/team/app"}},
+    {"type":"env","ops":[
+        {"op":"copy","from":"ENVLOCK_TEST_JAVA_HOME","to":"JDK_HOME"},
+        {"op":"move","from":"ENVLOCK_TEST_LEGACY","to":"MODERN"},
+        {"op":"replace","key":"REGISTRY_URL","pattern":"^https://registry\\.old\\.example/(\\w+)","replacement":"https://mirror.example/$1"},
+        {"op":"copy","from":"ENVLOCK_TEST_MISSING","to":"NEVER"}
+    ]}
+]}"#,
+    );
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .args(["--output", "json"])
+        .env("ENVLOCK_TEST_JAVA_HOME", "/opt/jdk")
+        .env("ENVLOCK_TEST_LEGACY", "value")
+        .env_remove("ENVLOCK_TEST_MISSING")
+        .output()
+        .expect("envlock command should run");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("output should be JSON");
+    assert_eq!(json["JDK_HOME"], "/opt/jdk");
+    assert_eq!(json["MODERN"], "value");
+    assert!(json["ENVLOCK_TEST_LEGACY"].is_null());
+    assert_eq!(json["REGISTRY_URL"], "https://mirror.example/team/app");
+    assert!(json.get("NEVER").is_none());
+}
+
+#[test]
+fn ops_chain_across_env_blocks_and_command_output() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = write_profile(
+        &temp,
+        "chain.json",
+        r#"{"injections":[
+    {"type":"env","ops":[{"op":"prepend","key":"ENVLOCK_TEST_PATH","value":"/first","separator":":"}]},
+    {"type":"command","program":"sh","args":["-c","echo export TOOL_HOME=/opt/tool"]},
+    {"type":"env","ops":[
+        {"op":"prepend","key":"ENVLOCK_TEST_PATH","value":"/second","separator":":"},
+        {"op":"set_if_absent","key":"TOOL_HOME","value":"/fallback"}
+    ]}
+]}"#,
+    );
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .args(["--output", "json"])
+        .env("ENVLOCK_TEST_PATH", "/usr/bin")
+        .env_remove("TOOL_HOME")
+        .output()
+        .expect("envlock command should run");
+    assert!(
+        output.status.success(),
+        "run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("output should be JSON");
+    assert_eq!(json["ENVLOCK_TEST_PATH"], "/second:/first:/usr/bin");
+    assert_eq!(json["TOOL_HOME"], "/opt/tool");
+}
+
+#[test]
+fn remove_and_normalize_clean_an_inherited_list() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let root = temp.path().canonicalize().expect("temp dir should resolve");
+    let bin = root.join("bin");
+    std::fs::create_dir_all(&bin).expect("bin dir should exist");
+    let inherited = [
+        bin.display().to_string(),
+        "/usr/local/bin".to_string(),
+        "/opt/conda/envs/base/bin".to_string(),
+        root.join("missing").display().to_string(),
+        format!("{}/../bin", bin.display()),
+    ]
+    .join(":");
+    let profile = write_profile(
+        &temp,
+        "clean.json",
+        r#"{"injections":[{"type":"env","ops":[
+    {"op":"remove","key":"ENVLOCK_TEST_PATH","value":"/usr/local/bin:/opt/conda/*","separator":":"},
+    {"op":"normalize","key":"ENVLOCK_TEST_PATH","separator":":"},
+    {"op":"remove","key":"ENVLOCK_TEST_ABSENT","value":"x"}
+]}]}"#,
+    );
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .args(["--output", "json"])
+        .env("ENVLOCK_TEST_PATH", &inherited)
+        .env_remove("ENVLOCK_TEST_ABSENT")
+        .output()
+        .expect("envlock command should run");
+    assert!(
+        output.status.success(),
+        "run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("output should be JSON");
+    assert_eq!(json["ENVLOCK_TEST_PATH"], bin.display().to_string());
+    assert!(json.get("ENVLOCK_TEST_ABSENT").is_none());
+}
@@ -0,0 +1,69 @@
+use serde_json::Value;
+use tempfile::TempDir;
+
+mod common;
+
+use common::{envlock, write_profile};
+
+#[test]
+fn typed_vars_and_empty_values_export_as_strings() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = write_profile(
+        &temp,
+        "values.yaml",
+        r#"injections:
+  - type: env
+    separator: ","
+    allow_empty: true
+    vars:
+      RETRIES: 3
+      RATIO: 0.5
+      VERBOSE: true
+      NO_PROXY: [localhost, 127.0.0.1]
+    ops:
+      - op: set
+        key: HTTP_PROXY
+        value: ""
+      - op: set_empty
+        key: HTTPS_PROXY
+"#,
+    );
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .args(["--output", "json"])
+        .output()
+        .expect("envlock command should run");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("output should be JSON");
+    assert_eq!(json["RETRIES"], "3");
+    assert_eq!(json["RATIO"], "0.5");
+    assert_eq!(json["VERBOSE"], "true");
+    assert_eq!(json["NO_PROXY"], "localhost,127.0.0.1");
+    assert_eq!(json["HTTP_PROXY"], "");
+    assert_eq!(json["HTTPS_PROXY"], "");
+}
+
+#[test]
+fn empty_set_requires_allow_empty() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = write_profile(
+        &temp,
+        "values.json",
+        r#"{"injections":[{"type":"env","ops":[{"op":"set","key":"NO_PROXY","value":""}]}]}"#,
+    );
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .args(["--output", "json"])
+        .output()
+        .expect("envlock command should run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("set `allow_empty` or use `set_empty`"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,70 @@
+use std::process::Command;
+
+use tempfile::TempDir;
+
+#[test]
+fn extends_by_name_resolves_under_envlock_home() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let envlock_home = temp.path().join("envlock-home");
+    let profiles_dir = envlock_home.join("profiles");
+    std::fs::create_dir_all(&profiles_dir).expect("profiles dir should be created");
+    std::fs::write(
+        profiles_dir.join("base.json"),
+        r#"{"injections":[{"type":"env","vars":{"ENVLOCK_BASE":"shared"}}]}"#,
+    )
+    .expect("base profile should be written");
+
+    let team = temp.path().join("team.json");
+    std::fs::write(
+        &team,
+        r#"{"extends":"base","injections":[{"type":"env","vars":{"ENVLOCK_TEAM":"alpha"}}]}"#,
+    )
+    .expect("team profile should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args([
+            "-p",
+            team.to_str().expect("team path should be UTF-8"),
+            "--output",
+            "json",
+            "--log-level",
+            "error",
+        ])
+        .env("ENVLOCK_HOME", &envlock_home)
+        .output()
+        .expect("envlock command should run");
+
+    assert!(
+        output.status.success(),
+        "run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(stdout.contains("\"ENVLOCK_BASE\": \"shared\""));
+    assert!(stdout.contains("\"ENVLOCK_TEAM\": \"alpha\""));
+}
+
+#[test]
+fn missing_parent_reports_extends_chain() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let child = temp.path().join("child.json");
+    std::fs::write(&child, r#"{"extends":"./missing.json"}"#)
+        .expect("child profile should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args([
+            "-p",
+            child.to_str().expect("child path should be UTF-8"),
+            "--log-level",
+            "error",
+        ])
+        .env("ENVLOCK_HOME", temp.path().join("envlock-home"))
+        .output()
+        .expect("envlock command should run");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).expect("stderr should be UTF-8");
+    assert!(stderr.contains("profile chain"));
+    assert!(stderr.contains("child.json -> "));
+    assert!(stderr.contains("missing.json"));
+}
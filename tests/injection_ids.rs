@@ -0,0 +1,50 @@
+use tempfile::TempDir;
+
+mod common;
+
+use common::{envlock, write_profile};
+
+#[test]
+fn enable_and_disable_toggle_injections_by_id() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = write_profile(
+        &temp,
+        "ids.json",
+        r#"{
+  "injections": [
+    { "type": "env", "id": "app", "depends_on": ["base"], "vars": { "LAYER": "app" } },
+    { "type": "env", "id": "base", "vars": { "LAYER": "base", "BASE": "1" } },
+    { "type": "env", "id": "debug", "enabled": false, "vars": { "DEBUG": "1" } }
+  ]
+}"#,
+    );
+    let run = |args: &[&str]| {
+        envlock(&temp)
+            .arg("-p")
+            .arg(&profile)
+            .args(args)
+            .output()
+            .expect("envlock command should run")
+    };
+
+    let output = run(&[]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert_eq!(stdout, "export BASE='1'\nexport LAYER='app'\n");
+
+    let output = run(&["--disable", "app", "--enable", "debug"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert_eq!(
+        stdout,
+        "export BASE='1'\nexport DEBUG='1'\nexport LAYER='base'\n"
+    );
+
+    let output = run(&["--disable", "nope"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("unknown injection id `nope` (known: app, base, debug)"),
+        "stderr: {stderr}"
+    );
+}
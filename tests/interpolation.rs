@@ -0,0 +1,55 @@
+use tempfile::TempDir;
+
+mod common;
+
+use common::{envlock, write_profile};
+
+const INTERPOLATE_PROFILE: &str = r#"{"injections":[
+    {"type":"env","vars":{"DB_HOST":"db"}},
+    {"type":"env","interpolate":true,"ops":[
+        {"op":"set","key":"DB_URL","value":"postgres://${DB_HOST}:${DB_PORT:-5432}/${ENVLOCK_TEST_DB}"},
+        {"op":"set","key":"PRICE","value":"$$5"}
+    ]}
+]}"#;
+
+#[test]
+fn interpolates_earlier_exports_and_defaults() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = write_profile(&temp, "interpolate.json", INTERPOLATE_PROFILE);
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .env_remove("DB_PORT")
+        .env_remove("ENVLOCK_TEST_DB")
+        .output()
+        .expect("envlock command should run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(
+        stdout.contains("export DB_URL='postgres://db:5432/'\n"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("export PRICE='$5'\n"), "{stdout}");
+}
+
+#[test]
+fn strict_rejects_unresolved_variables() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = write_profile(&temp, "interpolate.json", INTERPOLATE_PROFILE);
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .arg("--strict")
+        .env_remove("DB_PORT")
+        .env_remove("ENVLOCK_TEST_DB")
+        .output()
+        .expect("envlock command should run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("unresolved variable `ENVLOCK_TEST_DB`"),
+        "{stderr}"
+    );
+}
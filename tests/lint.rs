@@ -0,0 +1,69 @@
+use std::process::Command;
+
+use serde_json::Value;
+use tempfile::TempDir;
+
+#[test]
+fn lint_reports_rule_codes_and_fails_on_findings() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = temp.path().join("team.json");
+    std::fs::write(
+        &profile,
+        r#"{"injections":[
+            {"type":"env","vars":{"A":"1"}},
+            {"type":"env","id":"again","vars":{"A":"2"}},
+            {"type":"command","program":"envlock-no-such-program"}
+        ]}"#,
+    )
+    .expect("profile should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "lint"])
+        .arg(&profile)
+        .env("ENVLOCK_HOME", temp.path())
+        .output()
+        .expect("lint should run");
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(
+        stdout.contains("L002 overwritten-set #2 env `again`: `A` set in #1 env"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("L007 program-not-found #3 command"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("profile lint found 2 issue(s)"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "lint", "--output", "json"])
+        .arg(&profile)
+        .env("ENVLOCK_HOME", temp.path())
+        .output()
+        .expect("lint should run");
+    let json: Value = serde_json::from_slice(&output.stdout).expect("output should be JSON");
+    let codes: Vec<&str> = json[0]["findings"]
+        .as_array()
+        .expect("findings should be an array")
+        .iter()
+        .map(|finding| finding["code"].as_str().expect("code should be a string"))
+        .collect();
+    assert_eq!(codes, vec!["L002", "L007"]);
+}
+
+#[test]
+fn lint_passes_clean_profile() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = temp.path().join("clean.json");
+    std::fs::write(
+        &profile,
+        r#"{"injections":[{"type":"env","vars":{"A":"1"}}]}"#,
+    )
+    .expect("profile should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "lint"])
+        .arg(&profile)
+        .env("ENVLOCK_HOME", temp.path())
+        .output()
+        .expect("lint should run");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
@@ -0,0 +1,73 @@
+use serde_json::Value;
+use tempfile::TempDir;
+
+mod common;
+
+use common::{envlock, write_profile};
+
+const PARAMS_PROFILE: &str = r#"{
+  "params": {
+    "stage": { "required": true, "description": "deployment stage" },
+    "region": { "default": "eu-west-1" }
+  },
+  "injections": [
+    {
+      "type": "env",
+      "vars": {
+        "API_URL": "https://${param:stage}.example.com",
+        "REGION": "${param:region}"
+      }
+    }
+  ]
+}"#;
+
+#[test]
+fn set_flag_fills_params_on_run() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = write_profile(&temp, "params.json", PARAMS_PROFILE);
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .args(["--output", "json", "--set", "stage=prod"])
+        .args(["--set", "region=us-east-1"])
+        .output()
+        .expect("envlock command should run");
+    assert!(
+        output.status.success(),
+        "run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("output should be JSON");
+    assert_eq!(json["API_URL"], "https://prod.example.com");
+    assert_eq!(json["REGION"], "us-east-1");
+}
+
+#[test]
+fn missing_required_and_unknown_params_fail() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = write_profile(&temp, "params.json", PARAMS_PROFILE);
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .output()
+        .expect("envlock command should run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("missing required param `stage`"),
+        "stderr: {stderr}"
+    );
+
+    let output = envlock(&temp)
+        .arg("preview")
+        .arg("-p")
+        .arg(&profile)
+        .args(["--set", "stag=prod"])
+        .output()
+        .expect("envlock command should run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown param `stag`"), "stderr: {stderr}");
+}
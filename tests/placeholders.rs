@@ -0,0 +1,61 @@
+use serde_json::Value;
+use tempfile::TempDir;
+
+mod common;
+
+use common::{envlock, write_profile};
+
+#[test]
+fn run_expands_placeholders_in_env_values_and_command_args() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    // Profile and working directories are reported as real paths.
+    let root = temp.path().canonicalize().expect("temp dir should resolve");
+    let team = root.join("team");
+    std::fs::create_dir_all(&team).expect("team dir should exist");
+    let profile = write_profile(
+        &temp,
+        "team/profile.json",
+        r#"{"injections":[
+    {"type":"env","vars":{
+        "TOOLS":"${PROFILE_DIR}/tools",
+        "HOME_DIR":"${ENVLOCK_HOME}",
+        "RESOURCES":"${RESOURCE_HOME}",
+        "STARTED_IN":"${CWD}",
+        "OTHER":"${NOT_A_PLACEHOLDER}"
+    },"ops":[
+        {"op":"prepend","key":"ENVLOCK_TEST_PATH","value":"./bin","separator":":","path":true}
+    ]},
+    {"type":"command","program":"sh","args":["-c","echo export FROM_COMMAND=${PROFILE_DIR}"]}
+]}"#,
+    );
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .args(["--output", "json"])
+        .current_dir(temp.path())
+        .env_remove("ENVLOCK_RESOURCE_HOME")
+        .env("ENVLOCK_TEST_PATH", "/usr/bin")
+        .output()
+        .expect("envlock command should run");
+    assert!(
+        output.status.success(),
+        "run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("output should be JSON");
+    let path = |path: std::path::PathBuf| path.display().to_string();
+    assert_eq!(json["TOOLS"], path(team.join("tools")));
+    assert_eq!(json["HOME_DIR"], path(temp.path().join(".envlock")));
+    assert_eq!(
+        json["RESOURCES"],
+        path(temp.path().join(".envlock/resources"))
+    );
+    assert_eq!(json["STARTED_IN"], path(root));
+    assert_eq!(json["OTHER"], "${NOT_A_PLACEHOLDER}");
+    assert_eq!(
+        json["ENVLOCK_TEST_PATH"],
+        format!("{}:/usr/bin", team.join("bin").display())
+    );
+    assert_eq!(json["FROM_COMMAND"], path(team));
+}
@@ -230,9 +230,11 @@ fn plugin_node_preview_and_apply_emit_patch() {
     assert!(state_dir.join("versions/node/v24.12.0/bin/node").exists());
     assert!(state_dir.join("versions/npm/v10.9.2/bin/npm").exists());
     assert!(state_dir.join("versions/npm/v10.9.2/global/bin").is_dir());
-    assert!(state_dir
-        .join("versions/npm/v10.9.2/global/lib/node_modules")
-        .is_dir());
+    assert!(
+        state_dir
+            .join("versions/npm/v10.9.2/global/lib/node_modules")
+            .is_dir()
+    );
     assert!(state_dir.join("versions/pnpm/v10.30.3/bin/pnpm").exists());
     assert!(state_dir.join("versions/yarn/v1.22.22/bin/yarn").exists());
     assert!(state_dir.join("versions/yarn/v1.22.22/global").is_dir());
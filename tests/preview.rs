@@ -120,3 +120,238 @@ fn preview_json_has_stable_shape_without_sensitive_values() {
     assert!(!stdout.contains("\"A\": \"1\""));
     assert!(!stdout.contains("\"B\": \"2\""));
 }
+
+#[test]
+fn preview_json_reports_origin_of_included_injections() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let fragment = temp.path().join("proxy.json");
+    std::fs::write(
+        &fragment,
+        r#"{"injections":[{"type":"env","vars":{"HTTPS_PROXY":"http://proxy:3128"}}]}"#,
+    )
+    .expect("fragment should be written");
+    let profile = temp.path().join("main.json");
+    std::fs::write(
+        &profile,
+        r#"{
+  "injections": [
+    { "type": "env", "vars": { "A": "1" } },
+    { "type": "include", "path": "./proxy.json" }
+  ]
+}"#,
+    )
+    .expect("profile file should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args([
+            "preview",
+            "--profile",
+            profile.to_str().expect("profile path should be UTF-8"),
+            "--output",
+            "json",
+        ])
+        .output()
+        .expect("preview command should run");
+
+    assert!(
+        output.status.success(),
+        "preview failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("preview should be JSON");
+    let injections = json["injections"]
+        .as_array()
+        .expect("injections should be array");
+    assert_eq!(injections.len(), 2);
+    assert_eq!(injections[0]["origin"], profile.display().to_string());
+    assert_eq!(injections[1]["keys"][0], "HTTPS_PROXY");
+    assert_eq!(injections[1]["origin"], fragment.display().to_string());
+}
+
+#[test]
+fn preview_reports_why_injections_are_skipped() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = temp.path().join("conditional.json");
+    std::fs::write(
+        &profile,
+        r#"{
+  "injections": [
+    { "type": "env", "vars": { "A": "1" }, "when": { "os": "plan9" } },
+    { "type": "env", "vars": { "B": "2" }, "enabled": false },
+    { "type": "env", "vars": { "C": "3" }, "when": { "not": { "file_exists": "./missing.flag" } } }
+  ]
+}"#,
+    )
+    .expect("profile file should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args([
+            "preview",
+            "--profile",
+            profile.to_str().expect("profile path should be UTF-8"),
+            "--output",
+            "json",
+        ])
+        .output()
+        .expect("preview command should run");
+
+    assert!(
+        output.status.success(),
+        "preview failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("preview should be JSON");
+    let injections = json["injections"]
+        .as_array()
+        .expect("injections should be array");
+    assert_eq!(
+        injections[0]["skipped"],
+        format!("when: os == plan9 (actual: {})", std::env::consts::OS)
+    );
+    assert_eq!(injections[1]["skipped"], "disabled");
+    assert!(injections[2].get("skipped").is_none());
+}
+
+#[test]
+fn preview_lists_injections_in_run_order() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = temp.path().join("ordered.json");
+    std::fs::write(
+        &profile,
+        r#"{
+  "injections": [
+    { "type": "env", "id": "late", "depends_on": ["early"], "vars": { "B": "2" } },
+    { "type": "env", "id": "early", "vars": { "A": "1" } },
+    { "type": "env", "id": "optional", "enabled": false, "vars": { "C": "3" } }
+  ]
+}"#,
+    )
+    .expect("profile file should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args([
+            "preview",
+            "--profile",
+            profile.to_str().expect("profile path should be UTF-8"),
+            "--output",
+            "json",
+            "--enable",
+            "optional",
+            "--disable",
+            "late",
+        ])
+        .output()
+        .expect("preview command should run");
+
+    assert!(
+        output.status.success(),
+        "preview failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("preview should be JSON");
+    let injections = json["injections"]
+        .as_array()
+        .expect("injections should be array");
+    let ids: Vec<&str> = injections
+        .iter()
+        .map(|injection| injection["id"].as_str().expect("id should be set"))
+        .collect();
+    assert_eq!(ids, ["early", "late", "optional"]);
+    assert_eq!(injections[1]["skipped"], "disabled");
+    assert!(injections[2].get("skipped").is_none());
+}
+
+#[test]
+fn preview_shows_profile_meta() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = temp.path().join("meta.yaml");
+    std::fs::write(
+        &profile,
+        "meta:\n  name: Team\n  description: shared team env\n  tags: [work]\ninjections: []\n",
+    )
+    .expect("profile file should be written");
+    let profile = profile.to_str().expect("profile path should be UTF-8");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["preview", "--profile", profile])
+        .output()
+        .expect("preview command should run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(stdout.contains("name: Team\ndescription: shared team env\ntags: work\n"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["preview", "--profile", profile, "--output", "json"])
+        .output()
+        .expect("preview command should run");
+    let json: Value = serde_json::from_slice(&output.stdout).expect("preview should be JSON");
+    assert_eq!(json["meta"]["name"], "Team");
+    assert_eq!(json["meta"]["tags"][0], "work");
+}
+
+#[test]
+fn preview_lists_unset_keys() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = temp.path().join("unset.json");
+    std::fs::write(
+        &profile,
+        r#"{"injections":[{"type":"env","vars":{"KEEP":"1"},"ops":[{"op":"unset","key":"SECRET"}]}]}"#,
+    )
+    .expect("profile file should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args([
+            "preview",
+            "--profile",
+            profile.to_str().expect("profile path should be UTF-8"),
+        ])
+        .output()
+        .expect("preview command should run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(
+        stdout.contains("- [env] enabled=true keys=[KEEP] unset=[SECRET]"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn preview_lists_keys_and_ops_of_copy_move_and_replace() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = temp.path().join("ops.json");
+    std::fs::write(
+        &profile,
+        r#"{"injections":[
+    {"type":"env","vars":{"REGISTRY_URL":"https://registry.old.example/team/app"}},
+    {"type":"env","ops":[
+        {"op":"copy","from":"JAVA_HOME","to":"JDK_HOME"},
+        {"op":"move","from":"LEGACY","to":"MODERN"},
+        {"op":"replace","key":"REGISTRY_URL","pattern":"old","replacement":"new"}
+    ]}
+]}"#,
+    )
+    .expect("profile file should be written");
+    let profile = profile.to_str().expect("profile path should be UTF-8");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["preview", "--profile", profile])
+        .output()
+        .expect("preview command should run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(
+        stdout.contains(
+            "- [env] enabled=true keys=[JDK_HOME, MODERN, REGISTRY_URL] unset=[LEGACY] ops=[copy JAVA_HOME->JDK_HOME, move LEGACY->MODERN, replace REGISTRY_URL]"
+        ),
+        "{stdout}"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["preview", "--profile", profile, "--output", "json"])
+        .output()
+        .expect("preview command should run");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("preview should be JSON");
+    assert_eq!(json["injections"][1]["ops"][1], "move LEGACY->MODERN");
+    assert!(json["injections"][0].get("ops").is_none());
+}
@@ -0,0 +1,82 @@
+use std::process::Command;
+
+use serde_json::Value;
+use tempfile::TempDir;
+
+fn write_stack(temp: &TempDir) -> [String; 3] {
+    let files = [
+        (
+            "base.json",
+            r#"{"params":{"stage":{"default":"dev"}},"injections":[{"type":"env","vars":{"BASE":"1","LAYER":"base"}}]}"#,
+        ),
+        (
+            "team.json",
+            r#"{"injections":[{"type":"command","program":"sh","args":["-c","echo DERIVED=${BASE}-${param:stage}"]}]}"#,
+        ),
+        (
+            "local.json",
+            r#"{"injections":[{"type":"env","vars":{"LAYER":"local"}}]}"#,
+        ),
+    ];
+    files.map(|(name, content)| {
+        let path = temp.path().join(name);
+        std::fs::write(&path, content).expect("profile should be written");
+        path.to_str()
+            .expect("profile path should be UTF-8")
+            .to_string()
+    })
+}
+
+#[test]
+fn stacked_profiles_run_as_one_lifecycle() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let [base, team, local] = write_stack(&temp);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args([
+            "-p", &base, "-p", &team, "-p", &local, "--set", "stage=qa", "--output", "json",
+        ])
+        .output()
+        .expect("envlock should run");
+    assert!(
+        output.status.success(),
+        "stack failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("output should be JSON");
+    assert_eq!(json["DERIVED"], "1-qa");
+    assert_eq!(json["LAYER"], "local");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["-p", &base, "-p", &local, "--strict"])
+        .output()
+        .expect("envlock should run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("duplicate exported key detected in strict mode: LAYER"),
+        "stderr: {stderr}"
+    );
+}
+
+#[test]
+fn preview_shows_combined_plan_with_origins() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let [base, team, local] = write_stack(&temp);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args([
+            "preview", "-p", &base, "-p", &team, "-p", &local, "--output", "json",
+        ])
+        .output()
+        .expect("preview should run");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("preview should be JSON");
+    let origins: Vec<&str> = json["injections"]
+        .as_array()
+        .expect("injections should be an array")
+        .iter()
+        .map(|entry| entry["origin"].as_str().expect("origin should be a string"))
+        .collect();
+    assert_eq!(origins, vec![base.as_str(), team.as_str(), local.as_str()]);
+}
@@ -14,7 +14,9 @@ fn profiles_init_creates_default_profile() {
         .expect("envlock command should run");
 
     assert!(output.status.success());
-    assert!(envlock_home.join("profiles/default.json").is_file());
+    let written = std::fs::read_to_string(envlock_home.join("profiles/default.json"))
+        .expect("profile should be written");
+    assert!(written.contains("\"schema_version\": 1"));
 }
 
 #[test]
@@ -40,3 +42,336 @@ fn profiles_status_reports_existing_profiles() {
     assert!(stdout.contains("profiles_count: 1"));
     assert!(stdout.contains("default.json [ok]"));
 }
+
+#[test]
+fn profiles_status_lists_stage_overlays() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let envlock_home = temp.path().join("envlock-home");
+    let profiles = envlock_home.join("profiles");
+    std::fs::create_dir_all(&profiles).expect("profiles directory should be created");
+    std::fs::write(
+        profiles.join("app.json"),
+        r#"{"injections":[{"type":"env","vars":{"APP_MODE":"dev"}}]}"#,
+    )
+    .expect("profile should be written");
+    std::fs::write(
+        profiles.join("app.ci.yaml"),
+        "injections:\n  - type: env\n    vars:\n      APP_MODE: ci\n",
+    )
+    .expect("overlay should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "status"])
+        .env("ENVLOCK_HOME", &envlock_home)
+        .output()
+        .expect("envlock command should run");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(stdout.contains("app.json [ok] overlays=[ci]"), "{stdout}");
+    assert!(stdout.contains("profiles_count: 1"), "{stdout}");
+    assert!(!stdout.contains("- app.ci.yaml"), "{stdout}");
+}
+
+#[test]
+fn profiles_init_supports_yaml_and_toml() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let envlock_home = temp.path().join("envlock-home");
+
+    for (format, file) in [("yaml", "team.yaml"), ("toml", "team.toml")] {
+        let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+            .args([
+                "profiles", "init", "--type", "sample", "--format", format, "--name", "team",
+                "--force",
+            ])
+            .env("ENVLOCK_HOME", &envlock_home)
+            .output()
+            .expect("envlock command should run");
+        assert!(output.status.success());
+        assert!(envlock_home.join("profiles").join(file).is_file());
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "status"])
+        .env("ENVLOCK_HOME", &envlock_home)
+        .output()
+        .expect("envlock command should run");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(stdout.contains("profiles_count: 2"));
+    assert!(stdout.contains("team.toml [ok]"));
+    assert!(stdout.contains("team.yaml [ok]"));
+}
+
+#[test]
+fn profiles_init_rejects_name_extension_that_conflicts_with_format() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let envlock_home = temp.path().join("envlock-home");
+    let init = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_envlock"))
+            .args(["profiles", "init"])
+            .args(args)
+            .env("ENVLOCK_HOME", &envlock_home)
+            .output()
+            .expect("envlock command should run")
+    };
+
+    let output = init(&["--name", "team.yaml", "--format", "toml"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--name `team.yaml` is a yaml profile but --format is toml"),
+        "{stderr}"
+    );
+    assert!(!envlock_home.join("profiles/team.yaml").exists());
+
+    assert!(
+        init(&["--name", "team.yml", "--format", "yaml"])
+            .status
+            .success()
+    );
+    assert!(init(&["--name", "team.toml"]).status.success());
+    assert!(envlock_home.join("profiles/team.yml").is_file());
+    assert!(envlock_home.join("profiles/team.toml").is_file());
+}
+
+#[test]
+fn profiles_schema_matches_published_schema() {
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "schema"])
+        .output()
+        .expect("envlock command should run");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    let published = include_str!("../docs/public/profile.schema.json");
+    assert_eq!(
+        stdout, published,
+        "docs/public/profile.schema.json is stale; regenerate with `envlock profiles schema`"
+    );
+
+    let schema: serde_json::Value =
+        serde_json::from_str(published).expect("published schema should be JSON");
+    let variants = schema["$defs"]["InjectionProfile"]["oneOf"]
+        .as_array()
+        .expect("injection variants should be listed");
+    let property = |kind: &str, name: &str| {
+        variants
+            .iter()
+            .find(|variant| variant["properties"]["type"]["const"] == kind)
+            .map(|variant| variant["properties"][name]["default"].clone())
+            .unwrap_or_default()
+    };
+    assert_eq!(property("env", "enabled"), serde_json::json!(true));
+    assert_eq!(property("env", "allow_empty"), serde_json::json!(false));
+    assert_eq!(property("symlink", "cleanup"), serde_json::json!(true));
+    assert_eq!(property("symlink", "on_exist"), serde_json::json!("error"));
+    assert_eq!(
+        schema["$defs"]["ParamSpec"]["properties"]["required"]["default"],
+        serde_json::json!(false)
+    );
+}
+
+#[test]
+fn profiles_status_reports_unknown_field_location() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let envlock_home = temp.path().join("envlock-home");
+    let profiles = envlock_home.join("profiles");
+    std::fs::create_dir_all(&profiles).expect("profiles directory should be created");
+    std::fs::write(
+        profiles.join("typo.json"),
+        "{\n  \"injections\": [\n    { \"type\": \"symlink\", \"source\": \"a\", \"target\": \"b\", \"on_exists\": \"replace\" }\n  ]\n}\n",
+    )
+    .expect("profile should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "status"])
+        .env("ENVLOCK_HOME", &envlock_home)
+        .output()
+        .expect("envlock command should run");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(stdout.contains("typo.json [invalid: failed to parse JSON:"));
+    assert!(
+        stdout.contains("typo.json:3:56: unknown field `on_exists` (did you mean `on_exist`?)")
+    );
+}
+
+#[test]
+fn profiles_status_shows_meta_and_version_requirement() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let envlock_home = temp.path().join("envlock-home");
+    let profiles = envlock_home.join("profiles");
+    std::fs::create_dir_all(&profiles).expect("profiles directory should be created");
+    std::fs::write(
+        profiles.join("team.json"),
+        r#"{"meta":{"name":"Team","description":"shared team env","tags":["work","k8s"],"requires_envlock":">=0.1"},"injections":[]}"#,
+    )
+    .expect("profile should be written");
+    std::fs::write(
+        profiles.join("future.json"),
+        r#"{"meta":{"requires_envlock":">=99.0"},"injections":[]}"#,
+    )
+    .expect("profile should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "status"])
+        .env("ENVLOCK_HOME", &envlock_home)
+        .output()
+        .expect("envlock command should run");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(
+        stdout.contains(
+            r#"team.json [ok] name="Team" description="shared team env" tags=[work, k8s]"#
+        ),
+        "stdout: {stdout}"
+    );
+    assert!(stdout.contains("future.json [invalid:"));
+    assert!(stdout.contains("profile requires envlock >=99.0"));
+    assert!(stdout.contains("envlock self-update"));
+}
+
+#[test]
+fn unversioned_profile_with_typo_fails_to_load() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = temp.path().join("legacy.json");
+    std::fs::write(
+        &profile,
+        "{\n  \"injections\": [\n    { \"type\": \"env\", \"ops\": [{ \"op\": \"prepend\", \"key\": \"PATH\", \"value\": \"/bin\", \"separater\": \":\" }] }\n  ]\n}\n",
+    )
+    .expect("profile should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .arg("-p")
+        .arg(&profile)
+        .output()
+        .expect("envlock command should run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("unknown field `separater` (did you mean `separator`?)"),
+        "stderr: {stderr}"
+    );
+}
+
+#[test]
+fn profiles_migrate_stamps_schema_version() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = temp.path().join("legacy.json");
+    let original = "{\n  \"injections\": [\n    { \"type\": \"env\", \"vars\": { \"Z\": \"1\", \"A\": \"2\" } }\n  ]\n}\n";
+    std::fs::write(&profile, original).expect("profile should be written");
+    let path = profile.to_str().expect("profile path should be UTF-8");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .arg("-p")
+        .arg(&profile)
+        .output()
+        .expect("envlock command should run");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("profile has no schema_version, which is deprecated"),
+        "stderr: {stderr}"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "migrate", path])
+        .output()
+        .expect("envlock command should run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    let added: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.starts_with('+') && !line.starts_with("+++"))
+        .collect();
+    assert_eq!(added, ["+  \"schema_version\": 1,"], "stdout: {stdout}");
+    assert!(
+        !stdout
+            .lines()
+            .any(|line| line.starts_with("-") && !line.starts_with("---"))
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "migrate", path, "--write"])
+        .output()
+        .expect("envlock command should run");
+    assert!(output.status.success());
+    let migrated = std::fs::read_to_string(&profile).expect("profile should be readable");
+    assert_eq!(
+        migrated,
+        original.replacen("{\n", "{\n  \"schema_version\": 1,\n", 1)
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "migrate", path])
+        .output()
+        .expect("envlock command should run");
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(stdout.contains("profile already uses schema_version 1"));
+}
+
+#[test]
+fn profiles_fmt_check_and_rewrite() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let json = temp.path().join("team.json");
+    let yaml = temp.path().join("team.yaml");
+    std::fs::write(
+        &json,
+        r#"{"injections":[{"vars":{"B":"2","A":"1"},"type":"env","enabled":true}]}"#,
+    )
+    .expect("JSON profile should be written");
+    std::fs::write(&yaml, "injections:\n- type: env\n  vars:\n    A: '1'\n")
+        .expect("YAML profile should be written");
+
+    let check = || {
+        Command::new(env!("CARGO_BIN_EXE_envlock"))
+            .args(["profiles", "fmt", "--check"])
+            .arg(&json)
+            .arg(&yaml)
+            .output()
+            .expect("envlock command should run")
+    };
+
+    let output = check();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(stdout.contains(&format!("would reformat: {}", json.display())));
+    assert!(!stdout.contains("team.yaml"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "fmt"])
+        .arg(&json)
+        .output()
+        .expect("envlock command should run");
+    assert!(output.status.success());
+    assert_eq!(
+        std::fs::read_to_string(&json).expect("profile should be readable"),
+        "{\n  \"injections\": [\n    {\n      \"type\": \"env\",\n      \"vars\": {\n        \"A\": \"1\",\n        \"B\": \"2\"\n      }\n    }\n  ]\n}\n"
+    );
+    assert!(check().status.success());
+}
+
+#[test]
+fn profiles_fmt_keeps_commented_yaml() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let yaml = temp.path().join("a.yaml");
+    let original = "# shared team profile\ninjections:\n  - type: env # exported first\n    vars: { A: '1' }\n";
+    std::fs::write(&yaml, original).expect("YAML profile should be written");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_envlock"))
+        .args(["profiles", "fmt"])
+        .arg(&yaml)
+        .output()
+        .expect("envlock command should run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be UTF-8");
+    assert!(stdout.contains("skipped:"), "stdout: {stdout}");
+    assert_eq!(
+        std::fs::read_to_string(&yaml).expect("profile should be readable"),
+        original
+    );
+}
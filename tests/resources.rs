@@ -0,0 +1,73 @@
+use serde_json::Value;
+use tempfile::TempDir;
+
+mod common;
+
+use common::{envlock, write_profile};
+
+#[test]
+fn resource_uris_resolve_and_errors_name_the_uri_and_path() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let resources = temp.path().join("resources");
+    std::fs::create_dir_all(&resources).expect("resource home should exist");
+    std::fs::write(resources.join("token"), "secret\n").expect("token should be written");
+    std::fs::write(
+        resources.join("app.json"),
+        r#"{"db":{"host":"db.internal","port":5432}}"#,
+    )
+    .expect("config should be written");
+    let run = |vars: &str| {
+        let profile = write_profile(
+            &temp,
+            "resources.json",
+            &format!(r#"{{"injections":[{{"type":"env","vars":{vars}}}]}}"#),
+        );
+        envlock(&temp)
+            .arg("-p")
+            .arg(&profile)
+            .args(["--output", "json"])
+            .env("ENVLOCK_RESOURCE_HOME", &resources)
+            .output()
+            .expect("envlock command should run")
+    };
+
+    let output = run(r#"{
+        "TOKEN":"resource-content-trim://token",
+        "TOKEN_B64":"resource-base64://token",
+        "DB_HOST":"resource-json://app.json#/db/host",
+        "DB_PORT":"resource-json://app.json#/db/port"
+    }"#);
+    assert!(
+        output.status.success(),
+        "run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("output should be JSON");
+    assert_eq!(json["TOKEN"], "secret");
+    assert_eq!(json["TOKEN_B64"], "c2VjcmV0Cg==");
+    assert_eq!(json["DB_HOST"], "db.internal");
+    assert_eq!(json["DB_PORT"], "5432");
+
+    for (uri, message) in [
+        (
+            "resource-content://missing.txt",
+            "failed to read resource content",
+        ),
+        (
+            "resource-json://app.json#/db/user",
+            "JSON pointer `/db/user` not found",
+        ),
+    ] {
+        let output = run(&format!(r#"{{"VALUE":"{uri}"}}"#));
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let path = uri
+            .split("://")
+            .nth(1)
+            .and_then(|rest| rest.split('#').next());
+        let path = resources.join(path.expect("uri should have a path"));
+        assert!(stderr.contains(message), "{stderr}");
+        assert!(stderr.contains(uri), "{stderr}");
+        assert!(stderr.contains(&path.display().to_string()), "{stderr}");
+    }
+}
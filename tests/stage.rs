@@ -0,0 +1,61 @@
+use serde_json::Value;
+use tempfile::TempDir;
+
+mod common;
+
+use common::{envlock, write_profile};
+
+#[test]
+fn stage_overlay_is_appended_after_base_profile() {
+    let temp = TempDir::new().expect("temp dir should be created");
+    let profile = write_profile(
+        &temp,
+        "app.json",
+        r#"{"injections":[{"type":"env","vars":{"APP_MODE":"dev","APP_NAME":"demo"}}]}"#,
+    );
+    write_profile(
+        &temp,
+        "app.ci.yaml",
+        "injections:\n  - type: env\n    vars:\n      APP_MODE: ci\n",
+    );
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .args(["--output", "json"])
+        .env("ENVLOCK_STAGE", "ci")
+        .output()
+        .expect("envlock command should run");
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("output should be JSON");
+    assert_eq!(json["APP_MODE"], "ci");
+    assert_eq!(json["APP_NAME"], "demo");
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .args(["--stage", "prod", "--output", "json"])
+        .env("ENVLOCK_STAGE", "ci")
+        .output()
+        .expect("envlock command should run");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("output should be JSON");
+    assert_eq!(json["APP_MODE"], "dev");
+
+    let output = envlock(&temp)
+        .arg("-p")
+        .arg(&profile)
+        .args(["--stage", "prod", "--strict"])
+        .output()
+        .expect("envlock command should run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("no `prod` overlay for profile in strict mode"),
+        "stderr: {stderr}"
+    );
+}